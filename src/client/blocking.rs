@@ -0,0 +1,105 @@
+//! A synchronous [`Client`](crate::client::Client), for programs that don't run an async runtime.
+//!
+//! Each call drives the async client to completion on a runtime owned by the blocking client.
+//! It must not be used from within an async runtime.
+use crate::client::Client as AsyncClient;
+use crate::model::hoyolab::record::{Account, CredentialStatus, RecordCard};
+use crate::util::error::Result;
+use crate::util::types::Game;
+#[cfg(feature = "starrail")]
+use crate::model::starrail::chronicle::{challenge::Challenge, character::CharacterDetails, notes::StarRailNote, rogue::Rogue, stats::UserStats};
+
+
+#[derive(Debug)]
+pub struct Client {
+    inner: AsyncClient,
+    runtime: tokio::runtime::Runtime,
+}
+
+impl Client {
+    pub fn new() -> Result<Self> {
+        Self::from_async(AsyncClient::new())
+    }
+
+    /// Wraps an already configured async client.
+    pub fn from_async(inner: AsyncClient) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+        Ok(Client { inner, runtime })
+    }
+
+    /// The async client, to change its configuration.
+    pub fn get_mut(&mut self) -> &mut AsyncClient {
+        &mut self.inner
+    }
+
+    pub fn get_game_accounts(&self, lang: Option<&str>) -> Result<Vec<Account>> {
+        self.runtime.block_on(self.inner.get_game_accounts(lang))
+    }
+
+    pub fn verify_credentials(&self) -> Result<CredentialStatus> {
+        self.runtime.block_on(self.inner.verify_credentials())
+    }
+
+    pub fn get_game_account(&self, lang: Option<&str>, game: Game) -> Result<Account> {
+        self.runtime.block_on(self.inner.get_game_account(lang, game))
+    }
+
+    pub fn get_record_cards(&self, hoyolab_id: Option<u32>, lang: Option<&str>) -> Result<Vec<RecordCard>> {
+        self.runtime.block_on(self.inner.get_record_cards(hoyolab_id, lang))
+    }
+
+    #[cfg(feature = "starrail")]
+    pub fn get_starrail_note(&self, uid: Option<u32>, lang: Option<&str>, auto_auth: Option<bool>) -> Result<StarRailNote> {
+        self.runtime.block_on(self.inner.get_starrail_note(uid, lang, auto_auth))
+    }
+
+    #[cfg(feature = "starrail")]
+    pub fn get_starrail_user(&self, uid: Option<u32>, lang: Option<&str>) -> Result<UserStats> {
+        self.runtime.block_on(self.inner.get_starrail_user(uid, lang))
+    }
+
+    #[cfg(feature = "starrail")]
+    pub fn get_starrail_characters(&self, uid: Option<u32>, lang: Option<&str>) -> Result<Vec<CharacterDetails>> {
+        self.runtime.block_on(self.inner.get_starrail_characters(uid, lang))
+    }
+
+    #[cfg(feature = "starrail")]
+    pub fn get_starrail_challenge(&self, uid: Option<u32>, previous: Option<bool>, lang: Option<&str>) -> Result<Challenge> {
+        self.runtime.block_on(self.inner.get_starrail_challenge(uid, previous, lang))
+    }
+
+    #[cfg(feature = "starrail")]
+    pub fn get_starrail_rogue(&self, uid: Option<u32>, schedule_type: Option<i32>, lang: Option<&str>) -> Result<Rogue> {
+        self.runtime.block_on(self.inner.get_starrail_rogue(uid, schedule_type, lang))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use async_trait::async_trait;
+    use reqwest::StatusCode;
+    use crate::client::{HttpRequest, HttpResponse, Transport};
+    use crate::util::error::Error;
+
+    #[derive(Debug)]
+    struct Private;
+
+    #[async_trait]
+    impl Transport for Private {
+        async fn send(&self, _request: HttpRequest) -> Result<HttpResponse> {
+            Ok(HttpResponse::new(StatusCode::OK, r#"{"retcode":10102,"message":"Data is not public for the user","data":null}"#))
+        }
+    }
+
+    #[test]
+    fn runs_without_a_runtime() {
+        let mut client = Client::from_async(AsyncClient::with_transport(Arc::new(Private))).unwrap();
+        assert!(matches!(client.get_game_accounts(None), Err(Error::NotConfigured("cookies"))));
+
+        client.get_mut().set_cookies([("ltuid", "1000"), ("ltoken", "secret")]);
+        assert!(matches!(client.get_game_accounts(None), Err(Error::DataNotPublic { .. })));
+    }
+}
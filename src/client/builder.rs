@@ -0,0 +1,307 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use crate::client::cache::Cache;
+use crate::client::captcha::CaptchaSolver;
+use crate::client::cassette::{CassettePlayer, CassetteRecorder};
+use crate::client::client::Client;
+use crate::client::device::DeviceConfig;
+use crate::client::http::{HttpClient, HttpConfig, ProxyConfig};
+use crate::client::identity::ClientIdentity;
+use crate::client::manager::cookie::Cookies;
+use crate::client::manager::managers::{CookieListener, PoolEntry};
+use crate::client::profile::Profile;
+use crate::client::retry::RetryPolicy;
+use crate::client::transport::Transport;
+use crate::util::error::Result;
+use crate::util::types::{Game, Region};
+
+
+/// Configures a [`Client`]. Everything set here applies to the base client and to every game component.
+///
+/// ```no_run
+/// # use miHoYo_API_Wrapper::client::{Client, DeviceConfig};
+/// # use miHoYo_API_Wrapper::util::types::Game;
+/// let client = Client::builder()
+///     .cookies([("ltuid", "1000"), ("ltoken", "...")])
+///     .lang("ja-jp")
+///     .uid(Game::STARRAIL, 800000002)
+///     // Keeps the device id of each credential across restarts.
+///     .device_config(DeviceConfig::new().store("devices.json"))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Default)]
+pub struct ClientBuilder {
+    transport: Option<Arc<dyn Transport>>,
+    http: HttpConfig,
+    cassette: Option<CassetteMode>,
+    cookies: Option<Cookies>,
+    cookie_header: Option<String>,
+    cookie_pool: Vec<PoolEntry>,
+    lang: Option<String>,
+    region: Option<Region>,
+    proxy: Option<ProxyConfig>,
+    /// Max size and ttl.
+    cache: Option<(usize, Duration)>,
+    debug: Option<bool>,
+    uids: HashMap<Game, u32>,
+    hoyolab_id: Option<u32>,
+    retry: Option<RetryPolicy>,
+    identity: Option<ClientIdentity>,
+    device: Option<DeviceConfig>,
+    captcha_solver: Option<Arc<dyn CaptchaSolver>>,
+    cookie_listener: Option<CookieListener>,
+}
+
+#[derive(Debug)]
+enum CassetteMode {
+    Record(PathBuf),
+    Replay(PathBuf),
+}
+
+impl ClientBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cookie name and value pairs, e.g. `ltuid` and `ltoken`.
+    pub fn cookies<I, K, V>(mut self, cookies: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        let cookies = cookies.into_iter().map(|(k, v)| (k.into(), v.into()));
+        self.cookies = Some(Cookies::from_pairs(cookies));
+        self
+    }
+
+    /// A raw `Cookie` header as copied from the browser; [`build`](Self::build) fails when it holds no credentials.
+    pub fn cookie_header(mut self, header: impl Into<String>) -> Self {
+        self.cookie_header = Some(header.into());
+        self
+    }
+
+    /// Already parsed cookies.
+    pub fn credentials(mut self, cookies: Cookies) -> Self {
+        self.cookies = Some(cookies);
+        self
+    }
+
+    /// Credentials read-only requests are spread over; see [`Client::set_cookie_pool`].
+    pub fn cookie_pool<I, C>(mut self, pool: I) -> Self
+    where
+        I: IntoIterator<Item = C>,
+        C: Into<PoolEntry>,
+    {
+        self.cookie_pool = pool.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// The cookies, region, language and uids of a stored profile; later calls override them.
+    pub fn profile(mut self, profile: Profile) -> Self {
+        self.cookies = Some(profile.cookies);
+        self.region = profile.region.or(self.region);
+        self.lang = profile.lang.or(self.lang);
+        self.uids.extend(profile.uids);
+        self
+    }
+
+    /// The language used when a call doesn't ask for one, e.g. `"ja-jp"`.
+    pub fn lang(mut self, lang: impl Into<String>) -> Self {
+        self.lang = Some(lang.into());
+        self
+    }
+
+    pub fn region(mut self, region: Region) -> Self {
+        self.region = Some(region);
+        self
+    }
+
+    pub fn proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Keeps up to `max_size` game records for `ttl` per credential, so repeated calls don't reach HoYoLAB.
+    /// Real-time data, such as the daily note, is always fetched.
+    pub fn cache(mut self, max_size: usize, ttl: Duration) -> Self {
+        self.cache = Some((max_size, ttl));
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.http = self.http.timeout(timeout);
+        self
+    }
+
+    pub fn connect_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.http = self.http.connect_timeout(timeout);
+        self
+    }
+
+    /// Replaces the whole HTTP configuration, including the timeouts set so far.
+    pub fn http_config(mut self, config: HttpConfig) -> Self {
+        self.http = config;
+        self
+    }
+
+    /// Sends every request through `transport`; the HTTP configuration is then unused.
+    pub fn transport(mut self, transport: Arc<dyn Transport>) -> Self {
+        self.transport = Some(transport);
+        self
+    }
+
+    /// Writes every exchange to a cassette at `path`, redacted; see [`CassetteRecorder`].
+    /// Wraps the transport the client would otherwise use, reqwest unless [`transport`](Self::transport) was set.
+    pub fn record_to(mut self, path: impl Into<PathBuf>) -> Self {
+        self.cassette = Some(CassetteMode::Record(path.into()));
+        self
+    }
+
+    /// Answers every request from the cassette at `path` without touching the network; see [`CassettePlayer`].
+    pub fn replay_from(mut self, path: impl Into<PathBuf>) -> Self {
+        self.cassette = Some(CassetteMode::Replay(path.into()));
+        self
+    }
+
+    /// Logs raw response bodies at trace level.
+    pub fn debug(mut self, debug: bool) -> Self {
+        self.debug = Some(debug);
+        self
+    }
+
+    /// The uid used for `game` when a call doesn't name one.
+    pub fn uid(mut self, game: Game, uid: u32) -> Self {
+        self.uids.insert(game, uid);
+        self
+    }
+
+    pub fn hoyolab_id(mut self, hoyolab_id: u32) -> Self {
+        self.hoyolab_id = Some(hoyolab_id);
+        self
+    }
+
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    pub fn identity(mut self, identity: ClientIdentity) -> Self {
+        self.identity = Some(identity);
+        self
+    }
+
+    /// Where device fingerprints are registered and device ids are kept; see [`Client::set_device_config`].
+    pub fn device_config(mut self, config: DeviceConfig) -> Self {
+        self.device = Some(config);
+        self
+    }
+
+    pub fn captcha_solver(mut self, solver: Arc<dyn CaptchaSolver>) -> Self {
+        self.captcha_solver = Some(solver);
+        self
+    }
+
+    /// See [`Client::on_cookie_refresh`].
+    pub fn on_cookie_refresh(mut self, listener: impl Fn(&Cookies) + Send + Sync + 'static) -> Self {
+        self.cookie_listener = Some(CookieListener(Arc::new(listener)));
+        self
+    }
+
+    pub fn build(self) -> Result<Client> {
+        let transport = match (self.cassette, self.transport) {
+            (Some(CassetteMode::Replay(path)), _) => Arc::new(CassettePlayer::load(path)?),
+            (cassette, transport) => {
+                let transport = match transport {
+                    Some(transport) => transport,
+                    None => Arc::new(HttpClient::new(&self.http)?),
+                };
+                match cassette {
+                    Some(CassetteMode::Record(path)) => Arc::new(CassetteRecorder::new(path, transport)),
+                    _ => transport,
+                }
+            }
+        };
+        let mut client = Client::with_transport(transport);
+        if let Some(config) = self.device {
+            client.set_device_config(config)?;
+        }
+
+        if let Some(cookies) = self.cookies {
+            client.set_credentials(cookies);
+        }
+        if let Some(header) = &self.cookie_header {
+            client.set_cookie_header(header)?;
+        }
+        if !self.cookie_pool.is_empty() {
+            client.set_cookie_pool(self.cookie_pool);
+        }
+
+        let uids = (!self.uids.is_empty()).then_some(self.uids);
+        // Built once, so every component and every clone of the client shares it.
+        let cache = self.cache.map(|(max_size, ttl)| Arc::new(Cache::new(Some(max_size), Some(ttl))));
+        client.configure(|inner| {
+            if let Some(lang) = &self.lang {
+                inner.lang = lang.clone();
+            }
+            if let Some(region) = self.region {
+                inner.region = region;
+            }
+            if let Some(debug) = self.debug {
+                inner.debug = debug;
+            }
+            if let Some(retry) = &self.retry {
+                inner.retry = retry.clone();
+            }
+            if let Some(identity) = &self.identity {
+                inner.identity = identity.clone();
+            }
+            if let Some(cache) = &cache {
+                inner.cache = Some(cache.clone());
+            }
+            inner.proxy = self.proxy.clone();
+            inner.uid = uids.clone();
+            inner.hoyolab_id = self.hoyolab_id;
+            inner.captcha_solver = self.captcha_solver.clone();
+            inner.cookie_listener = self.cookie_listener.clone();
+        });
+        Ok(client)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn configures_every_component() {
+        let client = ClientBuilder::new()
+            .cookies([("ltuid", "1000"), ("ltoken", "secret")])
+            .lang("ja-jp")
+            .region(Region::CHINESE)
+            .uid(Game::STARRAIL, 100000001)
+            .debug(false)
+            .build()
+            .unwrap();
+
+        assert_eq!(client.client.lang, "ja-jp");
+        assert!(client.client.get_cookies().is_some());
+        #[cfg(feature = "starrail")]
+        {
+            let starrail = &client.starrail.0.0;
+            assert_eq!(starrail.region, Region::CHINESE);
+            assert_eq!(starrail.get_uid(&Game::STARRAIL).unwrap(), 100000001);
+            assert!(!starrail.debug);
+            assert!(starrail.get_cookies().is_some());
+        }
+
+        let profile = client.profile().unwrap();
+        assert_eq!(profile.uids[&Game::STARRAIL], 100000001);
+        let restored = ClientBuilder::new().profile(profile.clone()).build().unwrap();
+        assert_eq!(restored.profile().unwrap(), profile);
+    }
+}
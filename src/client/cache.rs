@@ -1,55 +1,77 @@
 use std::collections::HashMap;
-use async_trait::async_trait;
-use crate::util::types::GeneralAny;
-
-type CacheDict = HashMap<GeneralAny, (f64, GeneralAny)>;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 
 const MINUTE: u64 = 60;
 const HOUR: u64 = MINUTE * 60;
-const DAY: u64 = HOUR * 24;
-const WEEK: u64 = DAY * 7;
-
 
-#[async_trait]
-pub(crate) trait BaseCache { async fn get(&self, key: GeneralAny) -> Option<GeneralAny>;
-    async fn set(&self, key: GeneralAny, value: GeneralAny);
-    async fn get_static(&self, key: GeneralAny) -> Option<GeneralAny>;
-    async fn set_static(&self, key: GeneralAny, value: GeneralAny);
-}
 
+/// Decoded response data kept for `ttl`, shared by every component and clone of a client.
+/// Once `max_size` entries are held, expired entries are dropped first, then the one closest to expiring.
 #[derive(Debug)]
 pub(crate) struct Cache {
-    cache: CacheDict,
-    maxsize: i32,
-    ttl: u64,
-    static_ttl: u64
+    entries: Mutex<HashMap<String, (Instant, serde_json::Value)>>,
+    max_size: usize,
+    ttl: Duration,
 }
 
 impl Cache {
-    pub(crate) fn new(max: Option<i32>, ttl: Option<u64>, static_ttl: Option<u64>) -> Self {
+    pub(crate) fn new(max_size: Option<usize>, ttl: Option<Duration>) -> Self {
         Self {
-            cache: HashMap::default(),
-            maxsize: max.unwrap_or(1024),
-            ttl: ttl.unwrap_or(HOUR),
-            static_ttl: static_ttl.unwrap_or(DAY),
+            entries: Mutex::default(),
+            max_size: max_size.unwrap_or(1024),
+            ttl: ttl.unwrap_or(Duration::from_secs(HOUR)),
         }
     }
-}
 
-
-#[async_trait]
-impl BaseCache for Cache {
-    async fn get(&self, key: GeneralAny) -> Option<GeneralAny> {
-        todo!()
-    }
-    async fn set(&self, key: GeneralAny, value: GeneralAny) {
-        todo!()
+    pub(crate) fn get(&self, key: &str) -> Option<serde_json::Value> {
+        let mut entries = self.entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        match entries.get(key) {
+            Some((expires_at, value)) if *expires_at > Instant::now() => Some(value.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
     }
-    async fn get_static(&self, key: GeneralAny) -> Option<GeneralAny> {
-        todo!()
+
+    pub(crate) fn set(&self, key: String, value: serde_json::Value) {
+        if self.max_size == 0 {
+            return;
+        }
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if entries.len() >= self.max_size && !entries.contains_key(&key) {
+            entries.retain(|_, (expires_at, _)| *expires_at > now);
+            if entries.len() >= self.max_size {
+                let oldest = entries.iter().min_by_key(|(_, (expires_at, _))| *expires_at).map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+        entries.insert(key, (now + self.ttl, value));
     }
-    async fn set_static(&self, key: GeneralAny, value: GeneralAny) {
-        todo!()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expires_and_evicts_entries() {
+        let cache = Cache::new(Some(2), Some(Duration::from_secs(MINUTE)));
+        cache.set("a".to_string(), 1.into());
+        cache.set("b".to_string(), 2.into());
+        cache.set("c".to_string(), 3.into());
+        assert_eq!(cache.get("c"), Some(3.into()));
+        assert_eq!(["a", "b"].iter().filter(|key| cache.get(key).is_some()).count(), 1);
+
+        let cache = Cache::new(None, Some(Duration::ZERO));
+        cache.set("a".to_string(), 1.into());
+        assert_eq!(cache.get("a"), None);
     }
 }
@@ -0,0 +1,112 @@
+use std::fmt::Debug;
+use async_trait::async_trait;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use reqwest::header::HeaderMap;
+use serde::Deserialize;
+use crate::util::error::Result;
+use crate::util::secret::sensitive_header;
+
+
+/// A geetest challenge HoYoLAB wants solved before it answers (retcode 1034).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaptchaChallenge {
+    pub gt: String,
+    pub challenge: String,
+    /// Set when the challenge came with an `x-rpc-aigis` header; the answer is then sent back through it.
+    pub session_id: Option<String>,
+}
+
+/// The answer to a [`CaptchaChallenge`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaptchaSolution {
+    pub validate: String,
+    pub seccode: String,
+}
+
+impl CaptchaSolution {
+    /// A solution whose seccode is derived from `validate`, as the geetest widget does.
+    pub fn new(validate: impl Into<String>) -> Self {
+        let validate = validate.into();
+        let seccode = format!("{}|jordan", validate);
+        CaptchaSolution { validate, seccode }
+    }
+}
+
+/// Solves geetest challenges on behalf of a [`Client`](crate::client::Client), e.g. by asking a user or a solving service.
+/// Once solved, the original request is sent again with the answer attached.
+#[async_trait]
+pub trait CaptchaSolver: Debug + Send + Sync {
+    async fn solve(&self, challenge: &CaptchaChallenge) -> Result<CaptchaSolution>;
+}
+
+
+/// The `data` of `createVerification`.
+#[derive(Debug, Deserialize)]
+pub(crate) struct Verification {
+    pub(crate) gt: String,
+    pub(crate) challenge: String,
+}
+
+/// The `data` of `verifyVerification`: the challenge to send back with the retried request.
+#[derive(Debug, Deserialize)]
+pub(crate) struct VerifiedChallenge {
+    pub(crate) challenge: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Aigis {
+    session_id: String,
+    data: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct AigisData {
+    gt: String,
+    challenge: String,
+}
+
+impl CaptchaChallenge {
+    /// Reads the challenge out of an `x-rpc-aigis` response header.
+    pub(crate) fn from_aigis(header: &str) -> Option<CaptchaChallenge> {
+        let aigis = serde_json::from_str::<Aigis>(header).ok()?;
+        let data = serde_json::from_str::<AigisData>(&aigis.data).ok()?;
+        Some(CaptchaChallenge { gt: data.gt, challenge: data.challenge, session_id: Some(aigis.session_id) })
+    }
+
+    /// The headers the retried request carries the answer in.
+    pub(crate) fn answer_headers(&self, solution: &CaptchaSolution) -> Result<HeaderMap> {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-rpc-challenge", sensitive_header(&self.challenge)?);
+        headers.insert("x-rpc-validate", sensitive_header(&solution.validate)?);
+        headers.insert("x-rpc-seccode", sensitive_header(&solution.seccode)?);
+        if let Some(session_id) = &self.session_id {
+            let answer = serde_json::json!({
+                "geetest_challenge": self.challenge,
+                "geetest_validate": solution.validate,
+                "geetest_seccode": solution.seccode,
+            });
+            let aigis = format!("{};{}", session_id, STANDARD.encode(answer.to_string()));
+            headers.insert("x-rpc-aigis", sensitive_header(&aigis)?);
+        }
+        Ok(headers)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_aigis_challenge() {
+        let header = r#"{"session_id":"c4b2f8","mmt_type":1,"data":"{\"success\":1,\"gt\":\"e8aa\",\"challenge\":\"9f1c\",\"new_captcha\":1}"}"#;
+        let challenge = CaptchaChallenge::from_aigis(header).unwrap();
+        assert_eq!(challenge, CaptchaChallenge { gt: "e8aa".into(), challenge: "9f1c".into(), session_id: Some("c4b2f8".into()) });
+
+        let headers = challenge.answer_headers(&CaptchaSolution::new("v4l1d")).unwrap();
+        assert_eq!(headers["x-rpc-seccode"], "v4l1d|jordan");
+        assert!(headers.values().all(|value| value.is_sensitive()));
+        assert!(headers["x-rpc-aigis"].to_str().unwrap().starts_with("c4b2f8;"));
+    }
+}
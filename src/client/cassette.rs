@@ -0,0 +1,253 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use crate::client::transport::{HttpRequest, HttpResponse, Transport};
+use crate::util::error::{Error, Result};
+
+
+const REDACTED: &str = "<redacted>";
+const SECRET_HEADERS: &[&str] = &["cookie", "set-cookie", "ds", "x-rpc-device_fp", "x-rpc-challenge", "x-rpc-validate", "x-rpc-seccode", "x-rpc-aigis"];
+const SECRET_PARAMS: &[&str] = &["ltoken", "ltoken_v2", "cookie_token", "cookie_token_v2", "stoken", "authkey"];
+/// JSON body keys carrying what the headers above carry, e.g. the captcha answer of `verifyVerification`.
+const SECRET_BODY_KEYS: &[&str] = &["geetest_challenge", "geetest_validate", "geetest_seccode", "device_fp"];
+
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Cassette {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecordedRequest {
+    method: String,
+    url: String,
+    headers: BTreeMap<String, String>,
+    body: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecordedResponse {
+    status: u16,
+    headers: BTreeMap<String, String>,
+    body: String,
+}
+
+
+fn redact_headers(headers: &HeaderMap) -> BTreeMap<String, String> {
+    headers.iter()
+        .map(|(name, value)| {
+            let value = if SECRET_HEADERS.contains(&name.as_str()) {
+                REDACTED.to_string()
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).into_owned()
+            };
+            (name.to_string(), value)
+        })
+        .collect()
+}
+
+fn redact_url(url: &Url) -> Url {
+    let mut url = url.clone();
+    let pairs = url.query_pairs()
+        .map(|(key, value)| {
+            let value = if SECRET_PARAMS.contains(&key.as_ref()) { REDACTED.into() } else { value };
+            (key.into_owned(), value.into_owned())
+        })
+        .collect::<Vec<_>>();
+    if !pairs.is_empty() {
+        url.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+    url
+}
+
+/// Redacts the secret keys of a JSON body at any depth, along with the token parameters; other bodies are kept as is.
+fn redact_body(body: &[u8]) -> String {
+    fn redact(value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::Object(object) => {
+                for (key, value) in object.iter_mut() {
+                    if SECRET_BODY_KEYS.contains(&key.as_str()) || SECRET_PARAMS.contains(&key.as_str()) {
+                        *value = REDACTED.into();
+                    } else {
+                        redact(value);
+                    }
+                }
+            }
+            serde_json::Value::Array(values) => values.iter_mut().for_each(redact),
+            _ => {}
+        }
+    }
+
+    match serde_json::from_slice::<serde_json::Value>(body) {
+        Ok(mut json) => {
+            redact(&mut json);
+            json.to_string()
+        }
+        Err(_) => String::from_utf8_lossy(body).into_owned(),
+    }
+}
+
+/// What a replayed request is matched on: method, path and the (order independent) query.
+fn request_key(method: &str, url: &Url) -> (String, String, Vec<(String, String)>) {
+    let mut query = redact_url(url).query_pairs().into_owned().collect::<Vec<_>>();
+    query.sort();
+    (method.to_uppercase(), url.path().to_string(), query)
+}
+
+
+/// A [`Transport`] that forwards to `inner` and writes every exchange to a JSON cassette.
+/// Cookies, DS, token parameters and captcha answers are redacted before they reach the file.
+#[derive(Debug)]
+pub struct CassetteRecorder {
+    path: PathBuf,
+    inner: Arc<dyn Transport>,
+    cassette: Mutex<Cassette>,
+}
+
+impl CassetteRecorder {
+    pub fn new(path: impl AsRef<Path>, inner: Arc<dyn Transport>) -> Self {
+        CassetteRecorder { path: path.as_ref().to_path_buf(), inner, cassette: Mutex::default() }
+    }
+}
+
+#[async_trait]
+impl Transport for CassetteRecorder {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+        let recorded_request = RecordedRequest {
+            method: request.method.to_string(),
+            url: redact_url(&request.url).to_string(),
+            headers: redact_headers(&request.headers),
+            body: request.body.as_deref().map(redact_body),
+        };
+        let response = self.inner.send(request).await?;
+        let interaction = Interaction {
+            request: recorded_request,
+            response: RecordedResponse {
+                status: response.status.as_u16(),
+                headers: redact_headers(&response.headers),
+                body: String::from_utf8_lossy(&response.body).into_owned(),
+            },
+        };
+
+        // The whole cassette is rewritten each time so an interrupted run still leaves a valid file.
+        let contents = {
+            let mut cassette = self.cassette.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            cassette.interactions.push(interaction);
+            serde_json::to_vec_pretty(&*cassette)?
+        };
+        tokio::fs::write(&self.path, contents).await?;
+        Ok(response)
+    }
+}
+
+
+/// A [`Transport`] that answers from a cassette written by [`CassetteRecorder`] without touching the network.
+/// Identical requests are served in recorded order; once used up, the last one keeps being served.
+#[derive(Debug)]
+pub struct CassettePlayer {
+    interactions: Vec<Interaction>,
+    used: Mutex<Vec<bool>>,
+}
+
+impl CassettePlayer {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let contents = std::fs::read(path.as_ref())?;
+        let cassette = serde_json::from_slice::<Cassette>(&contents)?;
+        let used = vec![false; cassette.interactions.len()];
+        Ok(CassettePlayer { interactions: cassette.interactions, used: Mutex::new(used) })
+    }
+}
+
+#[async_trait]
+impl Transport for CassettePlayer {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+        let key = request_key(request.method.as_str(), &request.url);
+        let matches = self.interactions.iter()
+            .enumerate()
+            .filter(|(_, interaction)| {
+                Url::parse(&interaction.request.url)
+                    .is_ok_and(|url| request_key(&interaction.request.method, &url) == key)
+            })
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+
+        let index = {
+            let mut used = self.used.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            let index = matches.iter().copied().find(|index| !used[*index]).or(matches.last().copied())
+                .ok_or_else(|| Error::Cassette(format!("no recorded interaction for {} {}", key.0, request.url.path())))?;
+            used[index] = true;
+            index
+        };
+
+        let recorded = &self.interactions[index].response;
+        let status = StatusCode::from_u16(recorded.status)
+            .map_err(|why| Error::Cassette(why.to_string()))?;
+        let mut headers = HeaderMap::new();
+        for (name, value) in &recorded.headers {
+            if let (Ok(name), Ok(value)) = (HeaderName::try_from(name.as_str()), HeaderValue::from_str(value)) {
+                headers.append(name, value);
+            }
+        }
+        Ok(HttpResponse { status, headers, body: recorded.body.clone().into_bytes() })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::Method;
+
+    #[derive(Debug)]
+    struct Echo;
+
+    #[async_trait]
+    impl Transport for Echo {
+        async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+            Ok(HttpResponse::new(StatusCode::OK, format!(r#"{{"retcode":0,"message":"OK","data":"{}"}}"#, request.url.path())))
+        }
+    }
+
+    fn request(url: &str) -> HttpRequest {
+        let mut headers = HeaderMap::new();
+        headers.insert("cookie", HeaderValue::from_static("ltuid=1000; ltoken=secret"));
+        headers.insert("ds", HeaderValue::from_static("1690000000,abcdef,0123456789abcdef"));
+        HttpRequest { method: Method::GET, url: url.parse().unwrap(), headers, body: None, proxy: None }
+    }
+
+    #[tokio::test]
+    async fn records_redacted_and_replays() {
+        let path = std::env::temp_dir().join(format!("cassette-{}.json", std::process::id()));
+        let recorder = CassetteRecorder::new(&path, Arc::new(Echo));
+        recorder.send(request("https://bbs-api-os.hoyolab.com/game_record/hkrpg/api/note?role_id=800000002&server=prod_official_asia")).await.unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(!contents.contains("secret"));
+        assert!(!contents.contains("abcdef"));
+        assert!(contents.contains("role_id=800000002"));
+
+        let mut answer = request("https://bbs-api-os.hoyolab.com/game_record/card/wapi/verifyVerification");
+        answer.method = Method::POST;
+        answer.body = Some(br#"{"geetest_challenge":"9f1c","geetest_validate":"v4l1d","geetest_seccode":"v4l1d|jordan"}"#.to_vec());
+        recorder.send(answer).await.unwrap();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(!contents.contains("v4l1d") && !contents.contains("9f1c"));
+        assert!(contents.contains("geetest_seccode"));
+
+        let player = CassettePlayer::load(&path).unwrap();
+        let response = player.send(request("https://bbs-api-os.hoyolab.com/game_record/hkrpg/api/note?server=prod_official_asia&role_id=800000002")).await.unwrap();
+        assert!(String::from_utf8(response.body).unwrap().contains("hkrpg/api/note"));
+        assert!(matches!(player.send(request("https://bbs-api-os.hoyolab.com/game_record/hkrpg/api/rogue")).await, Err(Error::Cassette(_))));
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -1,220 +1,529 @@
-use std::sync::Arc;
 use std::collections::HashMap;
-use anyhow::{bail, Result};
-use reqwest::cookie::{CookieStore, Jar};
-use reqwest::header::{COOKIE, HeaderMap};
-use reqwest::{Response, Url};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Instant;
+use reqwest::header::{CONTENT_TYPE, COOKIE, HeaderMap, HeaderValue, SET_COOKIE, USER_AGENT};
+use reqwest::{Method, Url};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tracing::{Span, field, instrument};
 use crate::client::cache::Cache;
-use crate::client::manager::managers::BaseCookieManager;
+use crate::client::captcha::{CaptchaChallenge, CaptchaSolution, CaptchaSolver, Verification, VerifiedChallenge};
+use crate::client::endpoint::{BaseRoute, Endpoint, RecordParams};
+use crate::client::device::{Device, DeviceManager, random_hex, random_uuid};
+use crate::client::identity::ClientIdentity;
+use crate::client::http::{HttpClient, ProxyConfig};
+use crate::client::transport::{HttpRequest, HttpResponse, Transport};
+use crate::client::retry::{RetryClass, RetryPolicy};
+use crate::client::manager::managers::{BaseCookieManager, CookieListener, CredentialStore, RotatingCookieManager};
 use crate::client::routes::InternationalTrait;
+use crate::model::hoyolab::device::DeviceFp;
 use crate::model::hoyolab::record::{RecordCard, RecordCardList};
 use crate::model::ModelBase;
 use crate::util::{
     constants::*,
-    types::{AnyCookieOrHeader, Game, Region}
+    types::{Game, Region}
 };
-use crate::util::kwargs::Kwargs;
-use crate::util::types::StringDict;
-use crate::util::kwargs::get_ds_headers;
+use crate::util::error::{Error, Result};
+use crate::util::ds::{DsFamily, get_ds_headers};
+use crate::util::secret::sensitive_header;
+use crate::util::uid::{recognize_region, recognize_server};
 
 type Uid = HashMap<Game, u32>;
 
 
-#[derive(Debug)]
-pub(crate) struct InnerClient<'a> {
-    pub(crate) cookie_manager: Option<BaseCookieManager>,
-    pub(crate) authkey: Option<&'a str>,
-    pub(crate) lang: &'a str,
+/// Cloning is cheap: the transport, credentials, cache and device store are shared, not copied.
+#[derive(Debug, Clone)]
+pub(crate) struct InnerClient {
+    pub(crate) transport: Arc<dyn Transport>,
+    pub(crate) credentials: Arc<CredentialStore>,
+    /// The cookie pool read-only requests are spread over, when there is one.
+    pub(crate) rotation: Arc<RotatingCookieManager>,
+    /// Used when a call doesn't ask for a language.
+    pub(crate) lang: String,
     pub(crate) region: Region,
-    pub(crate) proxy: Option<&'a str>,
-    pub(crate) game: Option<Game>,
+    pub(crate) proxy: Option<ProxyConfig>,
     pub(crate) uid: Option<Uid>,
     pub(crate) hoyolab_id: Option<u32>,
-    pub(crate) cache: Option<Cache>,
+    pub(crate) cache: Option<Arc<Cache>>,
     pub(crate) debug: bool,
+    pub(crate) retry: RetryPolicy,
+    /// Overrides of `retry`, keyed by the trailing path of the endpoint.
+    pub(crate) endpoint_retry: HashMap<String, RetryPolicy>,
+    pub(crate) identity: ClientIdentity,
+    pub(crate) device: Arc<DeviceManager>,
+    pub(crate) captcha_solver: Option<Arc<dyn CaptchaSolver>>,
+    pub(crate) cookie_listener: Option<CookieListener>,
 }
 
 
-impl<'a> Default for InnerClient<'a> {
+impl Default for InnerClient {
     fn default() -> Self {
+        InnerClient::with_transport(Arc::new(HttpClient::default()))
+    }
+}
+
+
+impl InnerClient {
+    /// An unconfigured client that sends its requests through `transport`.
+    pub(crate) fn with_transport(transport: Arc<dyn Transport>) -> InnerClient {
         InnerClient {
-            cookie_manager: None,
-            authkey: None,
-            lang: "en-us",
+            transport,
+            credentials: Arc::default(),
+            rotation: Arc::default(),
+            lang: "en-us".to_string(),
             region: Region::OVERSEAS,
             proxy: None,
-            game: None,
             uid: None,
             hoyolab_id: None,
             cache: None,
             debug: true,
+            retry: RetryPolicy::default(),
+            endpoint_retry: HashMap::new(),
+            identity: ClientIdentity::default(),
+            device: Arc::default(),
+            captcha_solver: None,
+            cookie_listener: None,
         }
     }
-}
 
+    pub(crate) fn get_cookies(&self) -> Option<Arc<BaseCookieManager>> {
+        self.credentials.get()
+    }
 
-impl<'a> InnerClient<'a> {
-    pub(crate) fn new(cookies: Option<AnyCookieOrHeader>, authkey: Option<&'a str>, lang: &'a str, region: Region, proxy: Option<&'a str>, game: Option<Game>, uid: Option<Uid>, hoyolab_id: Option<u32>, cache: Option<Cache>, debug: bool) -> InnerClient<'a> {
-        let cookie_manager = Some(BaseCookieManager::from_cookies(cookies));
-        InnerClient {
-            cookie_manager, authkey, lang, region, proxy, game, uid, hoyolab_id, cache, debug,
-        }
+    pub(crate) fn get_hoyolab_id(&self) -> Result<u32> {
+        self.hoyolab_id.ok_or(Error::NotConfigured("hoyolab_id"))
     }
 
-    pub(crate) fn get_cookies(&self) -> Option<&BaseCookieManager> {
-        self.cookie_manager.as_ref()
+    fn get_region(&self) -> Region {
+        self.region
     }
 
-    pub(crate) fn get_hoyolab_id(&self) -> Result<u32> {
-        if let Some(hoyolab_id) = self.hoyolab_id.clone() {
-            return Ok(hoyolab_id);
+    pub(crate) fn get_uid(&self, game: &Game) -> Result<u32> {
+        self.uid.as_ref()
+            .and_then(|uid| uid.get(game).copied())
+            .ok_or(Error::NotConfigured("uid"))
+    }
+
+    fn get_retry_policy(&self, url: &str) -> &RetryPolicy {
+        let path = url.split('?').next().unwrap_or(url);
+        // The most specific override wins, whatever the order of the map.
+        self.endpoint_retry.iter()
+            .filter(|(endpoint, _)| path.ends_with(endpoint.as_str()))
+            .max_by_key(|(endpoint, _)| endpoint.len())
+            .map(|(_, policy)| policy)
+            .unwrap_or(&self.retry)
+    }
+
+    /// Serializes typed request parameters into query pairs.
+    fn forming_params<P: Serialize + ?Sized>(&self, params: &P) -> Result<Vec<(String, String)>> {
+        let query = serde_urlencoded::to_string(params)?;
+        Ok(url::form_urlencoded::parse(query.as_bytes()).into_owned().collect())
+    }
+
+    pub(crate) async fn request(
+        &self,
+        url: &str,
+        method: &str,
+        mut headers: HeaderMap,
+        params: &[(String, String)],
+        body: Option<&str>,
+    ) -> Result<HttpResponse> {
+        let cookies = self.get_cookies().ok_or(Error::NotConfigured("cookies"))?;
+        let mut parsed_url = url.parse::<Url>()
+            .map_err(|why| Error::Route(format!("invalid url `{}`: {}", url, why)))?;
+        if !params.is_empty() {
+            parsed_url.query_pairs_mut().extend_pairs(params);
+        }
+        let cookie = cookies.cookie_header(parsed_url.host_str().unwrap_or_default(), parsed_url.path())?;
+        headers.insert(COOKIE, sensitive_header(&cookie)?);
+        let method = method.parse::<Method>()
+            .map_err(|_| Error::Route(format!("unsupported http method `{}`", method)))?;
+        if let Some(user_agent) = &self.identity.user_agent {
+            headers.insert(USER_AGENT, HeaderValue::from_str(user_agent)?);
         }
-        bail!("")
+        if body.is_some() {
+            headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        }
+
+        // A proxy bound to the account wins over the one configured for the whole client.
+        let proxy = cookies.get_proxy().or(self.proxy.as_ref()).cloned();
+        let body = body.map(|body| body.as_bytes().to_vec());
+        let response = self.transport.send(HttpRequest { method, url: parsed_url, headers, body, proxy }).await?;
+        self.capture_cookies(&cookies, &response);
+        Ok(response)
     }
 
-    fn get_region(&self) -> Result<Region> {
-        Ok(self.region.clone())
+    /// Writes the tokens HoYoLAB rotated through `Set-Cookie` back to the credentials and tells the listener.
+    fn capture_cookies(&self, sent_with: &BaseCookieManager, response: &HttpResponse) {
+        let headers = response.headers.get_all(SET_COOKIE).iter().filter_map(|header| header.to_str().ok());
+        let Some(cookies) = self.credentials.refresh(sent_with, headers) else {
+            return;
+        };
+        tracing::debug!(account_id = ?cookies.account_id(), "credentials refreshed by the server");
+        if let Some(listener) = &self.cookie_listener {
+            (listener.0)(&cookies);
+        }
     }
 
-    fn get_uid(&self, game: &Game) -> Result<u32> {
-        if let Some(uid) = &self.uid {
-            return Ok(uid.get(game).unwrap().clone());
+    /// Registers `device` with the device-fp API of `region` and returns the fingerprint it assigns.
+    async fn fetch_device_fp(&self, region: Region, device: &Device, proxy: Option<ProxyConfig>) -> Result<String> {
+        let url = self.device.config.get_fp_url(region);
+        let body = serde_json::json!({
+            "device_id": device.device_id,
+            "seed_id": random_uuid(),
+            "seed_time": (self.identity.clock.now() * 1000).to_string(),
+            "platform": "5",
+            "device_fp": random_hex(13),
+            "app_name": match region {
+                Region::OVERSEAS => "bbs_oversea",
+                Region::CHINESE => "bbs_cn",
+            },
+            "ext_fields": "{}",
+        });
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        let request = HttpRequest {
+            method: Method::POST,
+            url: url.parse().map_err(|why| Error::Route(format!("invalid url `{}`: {}", url, why)))?,
+            headers,
+            body: Some(serde_json::to_vec(&body)?),
+            proxy,
+        };
+
+        let data = parse_response::<DeviceFp>(self.transport.send(request).await?)?;
+        if data.code != 200 {
+            return Err(Error::Api { retcode: data.code, message: data.msg });
         }
-        bail!("")
+        Ok(data.device_fp)
     }
 
-    fn forming_params(&self, kwargs: Kwargs) -> Vec<(String, String)> {
-        let mut base = vec![];
+    /// `x-rpc-device_id` and `x-rpc-device_fp` of the current credential, registering the fingerprint on first use.
+    /// A failed registration doesn't fail the request; it is attempted again once a cooldown has passed.
+    async fn device_headers(&self, region: Region) -> Result<HeaderMap> {
+        let mut headers = HeaderMap::new();
+        let Some(cookies) = self.get_cookies() else {
+            return Ok(headers);
+        };
+        let Some(account) = cookies.account_id() else {
+            return Ok(headers);
+        };
 
-        if let Some(params) = kwargs.get_pair::<Kwargs>("params") {
-            if let Some(pair) = params.1.get_pair::<u32>("uid") {
-                base.push((pair.0, pair.1.to_string()));
-            }
-            if let Some(pair) = params.1.get_pair::<u32>("role_id") {
-                base.push((pair.0, pair.1.to_string()));
-            }
-            if let Some(pair) = params.1.get_pair::<String>("server") {
-                base.push((pair.0, pair.1.to_string()));
-            }
-            if let Some(pair) = params.1.get_pair::<i32>("schedule_type") {
-                base.push((pair.0, pair.1.to_string()));
-            }
-            if let Some(pair) = params.1.get_pair::<&str>("need_all") {
-                base.push((pair.0, pair.1.to_string()));
+        let mut device = self.device.device(account).await?;
+        if device.device_fp.is_none() && self.device.may_register_fp(account) {
+            // Egresses like the requests of the account, so the fingerprint matches their address.
+            let proxy = cookies.get_proxy().or(self.proxy.as_ref()).cloned();
+            match self.fetch_device_fp(region, &device, proxy).await {
+                Ok(device_fp) => {
+                    self.device.set_fp(account, device_fp.clone()).await?;
+                    device.device_fp = Some(device_fp);
+                }
+                Err(why) => {
+                    tracing::debug!(error = %why, "device fingerprint unavailable");
+                    self.device.fp_failed(account);
+                }
             }
         }
 
-        base
+        headers.insert("x-rpc-device_id", HeaderValue::from_str(&device.device_id)?);
+        if let Some(device_fp) = &device.device_fp {
+            headers.insert("x-rpc-device_fp", HeaderValue::from_str(device_fp)?);
+        }
+        if region == Region::CHINESE {
+            headers.insert("x-rpc-device_name", HeaderValue::from_str(&self.device.config.device_name)?);
+        }
+        Ok(headers)
     }
 
-    pub(crate) async fn request(
-        &self,
-        url: &str,
-        method: &str,
-        mut headers: HeaderMap,
-        kwargs: Kwargs<'a>,
-    ) -> Result<Response> {
-        let jar = Jar::default();
-        let cookies = self.get_cookies().unwrap();
-        let (ltuid, ltoken) = cookies.forming_cookie();
-        jar.add_cookie_str(ltuid.as_str(), &url.parse::<Url>().unwrap());
-        jar.add_cookie_str(ltoken.as_str(), &url.parse::<Url>().unwrap());
-        headers.insert(COOKIE, jar.cookies(&url.parse::<Url>().unwrap()).unwrap());
-
-        let client = reqwest::Client::builder()
-            .user_agent(USER_AGENT)
-            .cookie_provider(Arc::new(jar))
-            .cookie_store(true)
-            .default_headers(headers.clone())
-            .build()
-            .unwrap();
-
-        let data = client.request(method.parse().unwrap(), url)
-            .query(&self.forming_params(kwargs))
-            .send()
-            .await
-            .unwrap();
-        Ok(data)
-    }
-
-    pub(crate) async fn request_hoyolab(
+    /// Sends the request and decodes its envelope, retrying as the endpoint's [`RetryPolicy`] allows.
+    /// Cookie and DS headers are never recorded; raw bodies are logged at trace level when `debug` is on.
+    #[allow(clippy::too_many_arguments)]
+    #[instrument(
+        skip_all,
+        fields(endpoint = url.split('?').next().unwrap_or(url), region = field::Empty, method = field::Empty, attempt = field::Empty, retcode = field::Empty, latency_ms = field::Empty),
+    )]
+    pub(crate) async fn request_hoyolab<T: DeserializeOwned, P: Serialize + ?Sized>(
         &self,
         url: &str,
         lang: Option<&str>,
         region: Option<Region>,
         method: Option<&str>,
-        _params: Option<StringDict>,
-        // data: Option<>,
+        params: &P,
+        data: Option<serde_json::Value>,
         headers: Option<HeaderMap>,
-        kwargs: Kwargs<'a>,
-    ) -> Result<Response> {
-        // ensure!(lang.is_none(),"lang were None");
-        // let lang = lang.unwrap_or(self.lang.clone());
-        let region = region.unwrap_or(self.get_region().unwrap());
+    ) -> Result<T> {
+        let lang = Some(lang.unwrap_or(&self.lang));
+        let region = region.unwrap_or(self.get_region());
+        Span::current().record("region", region.name());
         let url = if url.contains("https://") {
             url.to_string()
         } else {
-            format!("{}{}", TAKUMI_URL.get_url(region).unwrap(), url)
+            format!("{}{}", TAKUMI_URL.get_url(region)?, url)
         };
 
 
-        let mut new_headers = headers.unwrap_or_else(|| HeaderMap::new());
-        new_headers.extend(get_ds_headers(&region, lang));
+        let mut headers = headers.unwrap_or_default();
+        headers.extend(self.device_headers(region).await?);
+        // Serialized once, so the body that is signed is byte for byte the body that is sent.
+        let body = data.map(|data| serde_json::to_string(&data)).transpose()?;
+        let method = method.unwrap_or(if body.is_some() { "POST" } else { "GET" });
+        Span::current().record("method", method);
+        let params = self.forming_params(params)?;
+        let policy = self.get_retry_policy(&url);
+
+        let mut attempt = 1;
+        let mut answer: Option<HeaderMap> = None;
+        loop {
+            let mut new_headers = headers.clone();
+            if let Some(answer) = &answer {
+                new_headers.extend(answer.clone());
+            }
+
+            let started = Instant::now();
+            let result = self.send_signed(&url, method, new_headers, region, lang, &params, body.as_deref()).await;
 
-        let data = self.request(
-            url.as_str(),
-            method.unwrap_or("GET"),
-            new_headers,
-            kwargs
-        )
-            .await
-            .unwrap();
+            let span = Span::current();
+            span.record("attempt", attempt);
+            span.record("latency_ms", started.elapsed().as_millis() as u64);
+            match &result {
+                Ok(_) => { span.record("retcode", 0); }
+                Err(why) => {
+                    if let Some(retcode) = why.retcode() {
+                        span.record("retcode", retcode);
+                    }
+                    tracing::debug!(error = %why, "request failed");
+                }
+            }
 
-        Ok(data)
+            match result {
+                // A challenge is solved once; a second one in a row is handed to the caller.
+                Err(Error::CaptchaRequired { retcode, message, challenge }) if answer.is_none() => {
+                    let challenge = match challenge {
+                        Some(challenge) => challenge,
+                        None => match self.create_verification(region, lang).await {
+                            Ok(challenge) => challenge,
+                            Err(why) => {
+                                tracing::debug!(error = %why, "unable to fetch the captcha challenge");
+                                return Err(Error::CaptchaRequired { retcode, message, challenge: None });
+                            }
+                        },
+                    };
+                    let Some(solver) = self.captcha_solver.clone() else {
+                        return Err(Error::CaptchaRequired { retcode, message, challenge: Some(challenge) });
+                    };
+                    let solution = solver.solve(&challenge).await?;
+                    answer = Some(self.answer_challenge(region, lang, challenge, solution).await?);
+                }
+                Err(why) if policy.should_retry(&why, attempt, method.eq_ignore_ascii_case("GET")) => {
+                    tokio::time::sleep(policy.delay(attempt)).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Signs, sends and decodes a single attempt.
+    #[allow(clippy::too_many_arguments)]
+    async fn send_signed<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        method: &str,
+        mut headers: HeaderMap,
+        region: Region,
+        lang: Option<&str>,
+        params: &[(String, String)],
+        body: Option<&str>,
+    ) -> Result<T> {
+        // The DS carries a timestamp, so every attempt has to be signed again.
+        headers.extend(get_ds_headers(&self.identity, &region, DsFamily::of(url), body, params, lang)?);
+        let response = self.request(url, method, headers, params, body).await?;
+        if self.debug {
+            tracing::trace!(status = %response.status, body = %String::from_utf8_lossy(&response.body), "raw response");
+        }
+        parse_response(response)
+    }
+
+    /// Asks for the geetest challenge of the current credential.
+    async fn create_verification(&self, region: Region, lang: Option<&str>) -> Result<CaptchaChallenge> {
+        let url = format!("{}card/wapi/createVerification", RECORD_URL.get_url(region)?);
+        let params = [("is_high".to_string(), "true".to_string())];
+        let verification = self.send_signed::<Verification>(&url, "GET", HeaderMap::new(), region, lang, &params, None).await?;
+        Ok(CaptchaChallenge { gt: verification.gt, challenge: verification.challenge, session_id: None })
+    }
+
+    /// Hands the solution in and returns the headers the original request is retried with.
+    async fn answer_challenge(&self, region: Region, lang: Option<&str>, mut challenge: CaptchaChallenge, solution: CaptchaSolution) -> Result<HeaderMap> {
+        // Aigis challenges are answered in the retried request itself.
+        if challenge.session_id.is_none() {
+            let url = format!("{}card/wapi/verifyVerification", RECORD_URL.get_url(region)?);
+            let body = serde_json::json!({
+                "geetest_challenge": challenge.challenge,
+                "geetest_validate": solution.validate,
+                "geetest_seccode": solution.seccode,
+            }).to_string();
+            let verified = self.send_signed::<VerifiedChallenge>(&url, "POST", HeaderMap::new(), region, lang, &[], Some(&body)).await?;
+            challenge.challenge = verified.challenge;
+        }
+        challenge.answer_headers(&solution)
     }
 
 
-    pub(crate) async fn request_game_record(&self, endpoint: &str, lang: Option<&str>, region: Option<Region>, game: Option<Game>, kwargs: Option<Kwargs<'_>>) -> Result<Response> {
+    #[instrument(skip_all, fields(endpoint = endpoint, region = ?region, game = ?game))]
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn request_game_record<T: DeserializeOwned, P: Serialize + ?Sized>(&self, endpoint: &str, lang: Option<&str>, region: Option<Region>, game: Option<Game>, method: Option<&str>, params: &P, data: Option<serde_json::Value>) -> Result<T> {
+        let region = region.unwrap_or(self.get_region());
         let base_url = {
-            let mut url = RECORD_URL.get_url(region.unwrap_or(Region::OVERSEAS)).unwrap().to_string();
+            let mut url = RECORD_URL.get_url(region)?.to_string();
             if let Some(game) = game {
                 url = format!("{}{}/api/", url, game.name().to_lowercase());
             };
             url
         };
         let url = format!("{}{}", base_url, endpoint);
-        let kwargs = kwargs.unwrap_or_else(|| Kwargs::new());
 
-        let data = self.request_hoyolab(url.as_str(), lang, region, None, None, None, kwargs)
-            .await
-            .unwrap();
+        self.request_hoyolab(url.as_str(), lang, Some(region), method, params, data, None).await
+    }
 
-        Ok(data)
+    /// Sends `payload` as the query of `GET` requests and as the JSON body otherwise.
+    async fn request_payload<T: DeserializeOwned, P: Serialize>(&self, url: &str, lang: Option<&str>, region: Option<Region>, method: &Method, payload: &P) -> Result<T> {
+        if method == Method::GET {
+            self.request_hoyolab(url, lang, region, Some(method.as_str()), payload, None, None).await
+        } else {
+            let data = serde_json::to_value(payload)?;
+            self.request_hoyolab(url, lang, region, Some(method.as_str()), &(), Some(data), None).await
+        }
     }
 
-    pub(crate) async fn get_record_cards(&self, hoyolab_id: Option<u32>, lang: Option<&str>) -> Result<Vec<RecordCard>> {
-        let hoyolab_id = hoyolab_id.unwrap_or_else(|| self.get_hoyolab_id().unwrap());
-        // let cache_key = cache
+    /// Calls an endpoint about the in-game account `uid`, adding its `role_id` and `server`.
+    #[instrument(skip(self, payload), fields(server = field::Empty))]
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn request_role_record<T: DeserializeOwned, P: Serialize>(
+        &self, route: BaseRoute, endpoint: &str, game: Game, uid: u32, method: &Method, lang: Option<&str>, payload: P,
+    ) -> Result<T> {
+        let server = recognize_server(&uid, game)?;
+        Span::current().record("server", server.as_str());
+        let region = recognize_region(&uid, game);
+        let url = format!("{}{}", route.url(region.unwrap_or(self.get_region()), Some(game))?, endpoint);
+        let payload = RecordParams { role_id: uid, server, extra: payload };
+        self.request_payload(&url, lang, region, method, &payload).await
+    }
 
-        let mut kwargs = Kwargs::new();
-        let mut inner = Kwargs::new();
+    pub(crate) async fn call<E: Endpoint>(&self, endpoint: &E, lang: Option<&str>) -> Result<E::Response> {
+        match E::GAME {
+            Some(game) => {
+                let uid = match endpoint.uid() {
+                    Some(uid) => uid,
+                    None => self.get_uid(&game)?,
+                };
+                self.request_role_record(E::ROUTE, E::PATH, game, uid, &E::METHOD, lang, endpoint).await
+            }
+            None => {
+                let url = format!("{}{}", E::ROUTE.url(self.get_region(), None)?, E::PATH);
+                self.request_payload(&url, lang, None, &E::METHOD, endpoint).await
+            }
+        }
+    }
 
-        inner.set("uid", hoyolab_id);
-        kwargs.set("params", inner);
+    /// Sends `request` with a credential of the cookie pool, moving on to the next one when it is rate limited
+    /// or rejected. Without a pool the current credentials are used.
+    pub(crate) async fn rotate<T, F, Fut>(&self, request: F) -> Result<T>
+    where
+        F: Fn(InnerClient) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        if self.rotation.is_empty() {
+            return request(self.clone()).await;
+        }
 
-        let result = self.request_game_record(
+        let mut last_error = None;
+        while let Some(manager) = self.rotation.acquire() {
+            let mut client = self.clone();
+            let credentials = Arc::new(CredentialStore::with(manager.clone()));
+            client.credentials = credentials.clone();
+            // A rate limited credential is rested rather than retried.
+            client.retry = client.retry.without(RetryClass::RateLimited);
+            for policy in client.endpoint_retry.values_mut() {
+                *policy = policy.without(RetryClass::RateLimited);
+            }
+
+            let result = request(client).await;
+            self.rotation.report(&manager, &result);
+            if let Some(refreshed) = credentials.get().filter(|current| !Arc::ptr_eq(current, &manager)) {
+                self.rotation.replace(&manager, refreshed);
+            }
+            match result {
+                Err(why) if matches!(why, Error::InvalidCookies { .. } | Error::ExpiredCookies { .. })
+                    || RetryClass::of(&why) == Some(RetryClass::RateLimited) => last_error = Some(why),
+                result => return result,
+            }
+        }
+        Err(last_error.unwrap_or(Error::PoolExhausted))
+    }
+
+    /// Answers from the cache when the client has one and `key` is fresh in it for the current credential;
+    /// otherwise runs `request` and keeps its data.
+    // Only the game record components cache so far.
+    #[cfg_attr(not(feature = "starrail"), allow(dead_code))]
+    pub(crate) async fn cached<T, F, Fut>(&self, key: &str, request: F) -> Result<T>
+    where
+        T: DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<serde_json::Value>>,
+    {
+        let Some(cache) = &self.cache else {
+            return Ok(serde_json::from_value(request().await?)?);
+        };
+        let account = self.get_cookies().and_then(|cookies| cookies.account_id().map(str::to_string));
+        let key = format!("{}:{}", account.unwrap_or_default(), key);
+        let data = match cache.get(&key) {
+            Some(data) => data,
+            None => {
+                let data = request().await?;
+                cache.set(key, data.clone());
+                data
+            }
+        };
+        Ok(serde_json::from_value(data)?)
+    }
+
+    /// Public, so the request is spread over the cookie pool when there is one.
+    pub(crate) async fn get_record_cards(&self, hoyolab_id: Option<u32>, lang: Option<&str>) -> Result<Vec<RecordCard>> {
+        let hoyolab_id = match hoyolab_id {
+            Some(hoyolab_id) => hoyolab_id,
+            None => self.get_hoyolab_id()?,
+        };
+        self.rotate(|client| async move { client.fetch_record_cards(hoyolab_id, lang).await }).await
+    }
+
+    async fn fetch_record_cards(&self, hoyolab_id: u32, lang: Option<&str>) -> Result<Vec<RecordCard>> {
+        let data = self.request_game_record::<RecordCardList, _>(
             "card/wapi/getGameRecordCard",
             lang,
             None,
             None,
-            Some(kwargs)
-        )
-            .await
-            .unwrap();
-
-        let data = result.json::<ModelBase<RecordCardList>>()
-            .await
-            .unwrap();
-        Ok(data.data.list)
+            None,
+            &[("uid", hoyolab_id)],
+            None,
+        ).await?;
+        Ok(data.list)
+    }
+}
+
+
+/// Reads the response envelope and decodes its `data`, turning a non-zero retcode into an [`Error`].
+pub(crate) fn parse_response<T: DeserializeOwned>(response: HttpResponse) -> Result<T> {
+    match serde_json::from_slice::<ModelBase<serde_json::Value>>(&response.body) {
+        Ok(envelope) => envelope.into_data().map_err(|why| match why {
+            Error::CaptchaRequired { retcode, message, challenge: None } => {
+                let challenge = response.headers.get("x-rpc-aigis")
+                    .and_then(|header| header.to_str().ok())
+                    .and_then(CaptchaChallenge::from_aigis);
+                Error::CaptchaRequired { retcode, message, challenge }
+            }
+            why => why,
+        }),
+        Err(_) if !response.status.is_success() => Err(Error::Status(response.status)),
+        Err(why) => Err(Error::Decode(why)),
     }
 }
@@ -5,7 +5,7 @@ pub(crate) struct ChronicleCacheKey;
 
 
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) struct Chronicle<T>(pub(crate) T)
 where T: Send + Sync;
 
@@ -1,13 +1,17 @@
 use reqwest::Response;
+use std::sync::Arc;
+use crate::client::transport::Transport;
 use crate::client::component::base::InnerClient;
 use crate::client::component::chronicle::client::Chronicle;
-use crate::util::types::{Game, GeneralResult, Region};
+use crate::util::types::GeneralResult;
 
 
-#[derive(Debug)]
-pub(crate) struct GenshinClient(pub(crate) InnerClient<'static>);
+#[derive(Debug, Clone)]
+pub(crate) struct GenshinClient(pub(crate) InnerClient);
 
 
+// Stubs until the Genshin endpoints are ported.
+#[allow(unused)]
 impl GenshinClient {
     async fn inner_get_record(&self, endpoint: &str, uid: u32, method: Option<&str>, lang: Option<&str>, payload: Option<&str>, cache: Option<bool>) -> GeneralResult<Response> {
         todo!()
@@ -36,8 +40,8 @@ impl GenshinClient {
 
 
 impl Chronicle<GenshinClient> {
-    pub(crate) fn new() -> Self {
-        Chronicle(GenshinClient(InnerClient::default()))
+    pub(crate) fn new(transport: Arc<dyn Transport>) -> Self {
+        Chronicle(GenshinClient(InnerClient::with_transport(transport)))
     }
 
 }
@@ -1,18 +1,17 @@
-use async_trait::async_trait;
-use reqwest::header::HeaderMap;
 use reqwest::Response;
+use std::sync::Arc;
+use crate::client::transport::Transport;
 use crate::client::component::base::InnerClient;
 use crate::client::component::chronicle::client::Chronicle;
-use crate::client::manager::managers::BaseCookieManager;
-use crate::util::types::{Game,
- GeneralResult,
- Region};
+use crate::util::types::GeneralResult;
 
 
-#[derive(Debug)]
-pub(crate) struct HonkaiClient(pub(crate) InnerClient<'static>);
+#[derive(Debug, Clone)]
+pub(crate) struct HonkaiClient(pub(crate) InnerClient);
 
 
+// Stubs until the Honkai endpoints are ported.
+#[allow(unused)]
 impl HonkaiClient {
     async fn inner_get_record(&self, endpoint: &str, uid: u32, method: Option<&str>, lang: Option<&str>, payload: Option<&str>, cache: Option<bool>) -> GeneralResult<Response> {
         todo!()
@@ -41,8 +40,8 @@ impl HonkaiClient {
 
 
 impl Chronicle<HonkaiClient> {
-    pub(crate) fn new() -> Self {
-        Chronicle(HonkaiClient(InnerClient::default()))
+    pub(crate) fn new(transport: Arc<dyn Transport>) -> Self {
+        Chronicle(HonkaiClient(InnerClient::with_transport(transport)))
     }
 
 }
@@ -1,4 +1,8 @@
+#[cfg(any(feature = "genshin", feature = "honkai", feature = "starrail"))]
 pub mod client;
+#[cfg(feature = "genshin")]
 pub mod genshin;
+#[cfg(feature = "honkai")]
 pub mod honkai;
+#[cfg(feature = "starrail")]
 pub mod starrail;
@@ -1,116 +1,88 @@
-use reqwest::Response;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use reqwest::Method;
+use tracing::instrument;
+use std::sync::Arc;
+use crate::client::endpoint::BaseRoute;
+use crate::client::transport::Transport;
 use crate::client::component::base::InnerClient;
 use crate::client::component::chronicle::client::Chronicle;
-use crate::model::ModelBase;
 use crate::model::starrail::chronicle;
-use crate::util::kwargs::Kwargs;
-use crate::util::types::{Region, Game, GeneralResult};
-use crate::util::uid::recognize_region;
-use crate::util::uid::recognize_starrail_server;
+use crate::util::error::Result;
+use crate::util::types::Game;
 
 
-#[derive(Debug)]
-pub(crate) struct StarRailClient(pub(crate) InnerClient<'static>);
+#[derive(Debug, Serialize)]
+struct ChallengeParams {
+    schedule_type: i32,
+    need_all: bool,
+}
 
+#[derive(Debug, Serialize)]
+struct RogueParams {
+    schedule_type: i32,
+    need_detail: bool,
+}
 
-impl StarRailClient {
-    async fn inner_get_record<'a>(
-        &self, endpoint: &str, uid: u32, method: Option<&str>, lang: Option<&str>, payload: Option<Kwargs<'static>>, _cache: Option<bool>
-    ) -> GeneralResult<Response> {
-        let mut payload = payload.unwrap_or_else(|| Kwargs::new());
-        payload.set("role_id", uid);
-        payload.set("server", recognize_starrail_server(&uid).unwrap());
 
-        let mut kwargs = Kwargs::new();
+#[derive(Debug, Clone)]
+pub(crate) struct StarRailClient(pub(crate) InnerClient);
 
-        if method.unwrap_or("GET").eq("GET") {
-            kwargs.set("params", payload);
-        } else {
-            kwargs.set("data", payload);
-        };
 
-        let data = self.0.request_game_record(
-            endpoint,
-            lang,
-            recognize_region(&mut uid.clone(), Game::STARRAIL),
-            Some(Game::STARRAIL),
-            Some(kwargs)
-        )
-        .await
-        .unwrap();
-        Ok(data)
+impl StarRailClient {
+    fn get_uid(&self, uid: Option<u32>) -> Result<u32> {
+        match uid {
+            Some(uid) => Ok(uid),
+            None => self.0.get_uid(&Game::STARRAIL),
+        }
     }
 
-    pub(crate) async fn get_notes(&self, uid: Option<u32>, lang: Option<&str>, _auto_auth: Option<bool>) -> anyhow::Result<chronicle::notes::StarRailNote> {
-        let result = self.inner_get_record("note", uid.unwrap(), Some("GET"), lang, None, None)
-            .await
-            .unwrap()
-            .json::<ModelBase<chronicle::notes::StarRailNote>>()
-            .await
-            .unwrap();
-        Ok(result.data)
+    async fn inner_get_record<T: DeserializeOwned, P: Serialize>(
+        &self, endpoint: &str, uid: u32, method: Option<Method>, lang: Option<&str>, payload: P, cache: Option<bool>
+    ) -> Result<T> {
+        let method = method.unwrap_or(Method::GET);
+        if !cache.unwrap_or(true) {
+            return self.0.request_role_record(BaseRoute::Record, endpoint, Game::STARRAIL, uid, &method, lang, payload).await;
+        }
+        let key = format!("hkrpg:{}:{}:{}:{}", endpoint, uid, lang.unwrap_or(&self.0.lang), serde_json::to_string(&payload)?);
+        self.0.cached(&key, || self.0.request_role_record(BaseRoute::Record, endpoint, Game::STARRAIL, uid, &method, lang, payload)).await
     }
 
-    pub(crate) async fn get_user(&self, uid: Option<u32>, lang: Option<&str>) -> anyhow::Result<chronicle::stats::UserStats> {
-        let index_data = self.inner_get_record("index", uid.unwrap(), None, lang, None, None)
-            .await
-            .unwrap();
-        let basic_info = self.inner_get_record("role/basicInfo", uid.unwrap(), None, lang, None, None)
-            .await
-            .unwrap();
-        let partial_user = index_data.json::<ModelBase<chronicle::stats::PartialUserStats>>()
-            .await
-            .unwrap()
-            .data;
-        let little_info = basic_info.json::<ModelBase<chronicle::stats::UserLittleInfo>>()
-            .await
-            .unwrap()
-            .data;
-        Ok(chronicle::stats::UserStats::new(partial_user, little_info))
+    #[instrument(skip(self), fields(game = "hkrpg"))]
+    pub(crate) async fn get_notes(&self, uid: Option<u32>, lang: Option<&str>, _auto_auth: Option<bool>) -> Result<chronicle::notes::StarRailNote> {
+        self.inner_get_record("note", self.get_uid(uid)?, Some(Method::GET), lang, (), Some(false)).await
     }
 
-    pub(crate) async fn get_characters(&self, uid: Option<u32>, lang: Option<&str>) -> anyhow::Result<Vec<chronicle::character::CharacterDetails>>{
-        let result = self.inner_get_record("avatar/info", uid.unwrap(), None, lang, None, None)
-            .await
-            .unwrap()
-            .json::<ModelBase<chronicle::character::Characters>>()
-            .await
-            .unwrap()
-            .data;
-        Ok(result.list)
+    #[instrument(skip(self), fields(game = "hkrpg"))]
+    pub(crate) async fn get_user(&self, uid: Option<u32>, lang: Option<&str>) -> Result<chronicle::stats::UserStats> {
+        let uid = self.get_uid(uid)?;
+        let partial_user = self.inner_get_record::<chronicle::stats::PartialUserStats, _>("index", uid, None, lang, (), None).await?;
+        let little_info = self.inner_get_record::<chronicle::stats::UserLittleInfo, _>("role/basicInfo", uid, None, lang, (), None).await?;
+        Ok(chronicle::stats::UserStats::new(partial_user, little_info))
     }
 
-    pub(crate) async fn get_challenge(&self, uid: Option<u32>, previous: Option<bool>, lang: Option<&str>) -> anyhow::Result<chronicle::challenge::Challenge> {
-        let mut payload = Kwargs::new();
-        payload.set("schedule_type", if previous.is_some() { 2 } else { 1 });
-        payload.set("need_all", "true");
+    #[instrument(skip(self), fields(game = "hkrpg"))]
+    pub(crate) async fn get_characters(&self, uid: Option<u32>, lang: Option<&str>) -> Result<Vec<chronicle::character::CharacterDetails>>{
+        let characters = self.inner_get_record::<chronicle::character::Characters, _>("avatar/info", self.get_uid(uid)?, None, lang, (), None).await?;
+        Ok(characters.list)
+    }
 
-        let result = self.inner_get_record("challenge", uid.unwrap(), None, lang, Some(payload), None)
-            .await
-            .unwrap()
-            .json::<ModelBase<chronicle::challenge::Challenge>>()
-            .await
-            .unwrap();
-        Ok(result.data)
+    #[instrument(skip(self), fields(game = "hkrpg"))]
+    pub(crate) async fn get_challenge(&self, uid: Option<u32>, previous: Option<bool>, lang: Option<&str>) -> Result<chronicle::challenge::Challenge> {
+        let payload = ChallengeParams { schedule_type: if previous.is_some() { 2 } else { 1 }, need_all: true };
+        self.inner_get_record("challenge", self.get_uid(uid)?, None, lang, payload, None).await
     }
 
-    pub(crate) async fn get_rouge(&self, uid: Option<u32>, schedule_type: Option<i32>, lang: Option<&str>) -> anyhow::Result<chronicle::rogue::Rogue> {
-        let mut payload = Kwargs::new();
-        payload.set("schedule_type", schedule_type.unwrap_or(3));
-        payload.set("need_detail", "true");
-        let result = self.inner_get_record("rogue", uid.unwrap(), None, lang, Some(payload), None)
-            .await
-            .unwrap()
-            .json::<ModelBase<chronicle::rogue::Rogue>>()
-            .await
-            .unwrap();
-        Ok(result.data)
+    #[instrument(skip(self), fields(game = "hkrpg"))]
+    pub(crate) async fn get_rouge(&self, uid: Option<u32>, schedule_type: Option<i32>, lang: Option<&str>) -> Result<chronicle::rogue::Rogue> {
+        let payload = RogueParams { schedule_type: schedule_type.unwrap_or(3), need_detail: true };
+        self.inner_get_record("rogue", self.get_uid(uid)?, None, lang, payload, None).await
     }
 }
 
 
 impl Chronicle<StarRailClient> {
-    pub(crate) fn new() -> Self {
-        Chronicle(StarRailClient(InnerClient::default()))
+    pub(crate) fn new(transport: Arc<dyn Transport>) -> Self {
+        Chronicle(StarRailClient(InnerClient::with_transport(transport)))
     }
 }
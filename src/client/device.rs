@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use crate::client::routes::InternationalTrait;
+use crate::util::constants::DEVICE_FP_URL;
+use crate::util::error::Result;
+use crate::util::types::Region;
+
+
+/// Where device fingerprints are registered and where the device of each credential is kept.
+#[derive(Debug, Clone)]
+pub struct DeviceConfig {
+    pub(crate) os_fp_url: String,
+    pub(crate) cn_fp_url: String,
+    pub(crate) store: Option<PathBuf>,
+    pub(crate) device_name: String,
+}
+
+impl Default for DeviceConfig {
+    fn default() -> Self {
+        DeviceConfig {
+            os_fp_url: DEVICE_FP_URL.get_url(Region::OVERSEAS).unwrap_or_default().to_string(),
+            cn_fp_url: DEVICE_FP_URL.get_url(Region::CHINESE).unwrap_or_default().to_string(),
+            store: None,
+            device_name: "iPhone".to_string(),
+        }
+    }
+}
+
+impl DeviceConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The device-fp API of `region`.
+    pub fn fp_url(mut self, region: Region, url: impl Into<String>) -> Self {
+        match region {
+            Region::OVERSEAS => self.os_fp_url = url.into(),
+            Region::CHINESE => self.cn_fp_url = url.into(),
+        }
+        self
+    }
+
+    /// A JSON file the device ids and fingerprints are kept in, so they survive restarts.
+    /// There is none by default: devices then live as long as the client.
+    pub fn store(mut self, path: impl AsRef<Path>) -> Self {
+        self.store = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Sent as `x-rpc-device_name` on mainland requests.
+    pub fn device_name(mut self, name: impl Into<String>) -> Self {
+        self.device_name = name.into();
+        self
+    }
+
+    pub(crate) fn get_fp_url(&self, region: Region) -> &str {
+        match region {
+            Region::OVERSEAS => &self.os_fp_url,
+            Region::CHINESE => &self.cn_fp_url,
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Device {
+    pub(crate) device_id: String,
+    pub(crate) device_fp: Option<String>,
+}
+
+/// How long a credential waits before its fingerprint registration is attempted again after a failure.
+const FP_RETRY_AFTER: Duration = Duration::from_secs(5 * 60);
+
+
+/// The device of every credential, keyed by account id. The store is rewritten on every change.
+#[derive(Debug, Default)]
+pub(crate) struct DeviceManager {
+    pub(crate) config: DeviceConfig,
+    devices: Mutex<HashMap<String, Device>>,
+    /// When the fingerprint registration of an account last failed.
+    failed_fp: Mutex<HashMap<String, Instant>>,
+}
+
+impl DeviceManager {
+    pub(crate) fn new(config: DeviceConfig) -> Result<DeviceManager> {
+        let devices = match &config.store {
+            Some(path) => match std::fs::read(path) {
+                Ok(contents) => serde_json::from_slice(&contents)?,
+                Err(why) if why.kind() == ErrorKind::NotFound => HashMap::new(),
+                Err(why) => return Err(why.into()),
+            },
+            None => HashMap::new(),
+        };
+        Ok(DeviceManager { config, devices: Mutex::new(devices), failed_fp: Mutex::default() })
+    }
+
+    /// The device of `account`; a new id is generated and persisted on first use.
+    pub(crate) async fn device(&self, account: &str) -> Result<Device> {
+        let (device, created) = {
+            let mut devices = self.devices.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            match devices.get(account) {
+                Some(device) => (device.clone(), false),
+                None => {
+                    let device = Device { device_id: random_uuid(), device_fp: None };
+                    devices.insert(account.to_string(), device.clone());
+                    (device, true)
+                }
+            }
+        };
+        if created {
+            self.save().await?;
+        }
+        Ok(device)
+    }
+
+    pub(crate) async fn set_fp(&self, account: &str, device_fp: String) -> Result<()> {
+        self.failed_fp.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).remove(account);
+        {
+            let mut devices = self.devices.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            if let Some(device) = devices.get_mut(account) {
+                device.device_fp = Some(device_fp);
+            }
+        }
+        self.save().await
+    }
+
+    /// Whether the fingerprint of `account` may be registered now, i.e. it didn't just fail.
+    pub(crate) fn may_register_fp(&self, account: &str) -> bool {
+        let failed_fp = self.failed_fp.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        failed_fp.get(account).is_none_or(|failed_at| failed_at.elapsed() >= FP_RETRY_AFTER)
+    }
+
+    pub(crate) fn fp_failed(&self, account: &str) {
+        let mut failed_fp = self.failed_fp.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        failed_fp.insert(account.to_string(), Instant::now());
+    }
+
+    async fn save(&self) -> Result<()> {
+        let Some(path) = &self.config.store else {
+            return Ok(());
+        };
+        let contents = {
+            let devices = self.devices.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            serde_json::to_vec_pretty(&*devices)?
+        };
+        tokio::fs::write(path, contents).await?;
+        Ok(())
+    }
+}
+
+
+/// A random (version 4) UUID.
+pub(crate) fn random_uuid() -> String {
+    let mut bytes = rand::thread_rng().gen::<[u8; 16]>();
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = bytes.iter().map(|byte| format!("{:02x}", byte)).collect::<String>();
+    format!("{}-{}-{}-{}-{}", &hex[0..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..32])
+}
+
+pub(crate) fn random_hex(len: usize) -> String {
+    let mut rng = rand::thread_rng();
+    (0..len).map(|_| char::from_digit(rng.gen_range(0..16), 16).unwrap_or('0')).collect()
+}
@@ -0,0 +1,91 @@
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use crate::client::routes::InternationalTrait;
+use crate::util::constants::{ACCOUNT_URL, BBS_URL, COMMUNITY_URL, RECORD_URL, TAKUMI_URL, WEB_API_URL};
+use crate::util::error::Result;
+use crate::util::types::{Game, Region};
+
+
+/// The host an [`Endpoint`] lives under. Each one resolves to its overseas or mainland url.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BaseRoute {
+    Takumi,
+    /// Battle chronicle; endpoints of a game are placed under `{game}/api/`.
+    Record,
+    Bbs,
+    Community,
+    Account,
+    WebApi,
+    /// A fixed url, used as is in every region.
+    Url(&'static str),
+}
+
+impl BaseRoute {
+    pub(crate) fn url(&self, region: Region, game: Option<Game>) -> Result<String> {
+        let url = match self {
+            BaseRoute::Takumi => TAKUMI_URL.get_url(region)?,
+            BaseRoute::Record => {
+                let url = RECORD_URL.get_url(region)?;
+                return Ok(match game {
+                    Some(game) => format!("{}{}/api/", url, game.name()),
+                    None => url.to_string(),
+                });
+            }
+            BaseRoute::Bbs => BBS_URL.get_url(region)?,
+            BaseRoute::Community => COMMUNITY_URL.get_url(region)?,
+            BaseRoute::Account => ACCOUNT_URL.get_url(region)?,
+            BaseRoute::WebApi => WEB_API_URL.get_url(region)?,
+            BaseRoute::Url(url) => url,
+        };
+        Ok(url.to_string())
+    }
+}
+
+
+/// Describes a HoYoLAB API call: where it is, how it is sent and what it answers.
+/// The implementing type is the request parameters; it is sent as the query of `GET` requests
+/// and as the JSON body otherwise. Execute it with [`Client::call`](crate::client::Client::call).
+///
+/// When `GAME` is set, the call is about an in-game account: `role_id` and `server` are added
+/// to the parameters and the region is taken from the uid.
+pub trait Endpoint: Serialize + Sync {
+    type Response: DeserializeOwned;
+
+    const ROUTE: BaseRoute;
+    /// Appended to the route, e.g. `"note"`.
+    const PATH: &'static str;
+    const METHOD: Method = Method::GET;
+    const GAME: Option<Game> = None;
+
+    /// The uid the call is about; the one configured on the client is used otherwise.
+    fn uid(&self) -> Option<u32> {
+        None
+    }
+}
+
+
+/// The parameters of a game record call: the role, its server and the endpoint's own fields.
+#[derive(Debug, Serialize)]
+pub(crate) struct RecordParams<P> {
+    pub(crate) role_id: u32,
+    pub(crate) server: String,
+    #[serde(flatten)]
+    pub(crate) extra: P,
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_route_is_placed_under_the_game() {
+        assert_eq!(
+            BaseRoute::Record.url(Region::OVERSEAS, Some(Game::STARRAIL)).unwrap(),
+            "https://bbs-api-os.hoyolab.com/game_record/hkrpg/api/"
+        );
+        assert_eq!(BaseRoute::Record.url(Region::CHINESE, None).unwrap(), "https://api-takumi-record.mihoyo.com/game_record/app/");
+        assert_eq!(BaseRoute::Url("https://example.com/").url(Region::CHINESE, Some(Game::GENSHIN)).unwrap(), "https://example.com/");
+    }
+}
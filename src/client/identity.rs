@@ -0,0 +1,79 @@
+use std::sync::Arc;
+use crate::util::constants::{DS_SALT, DS_SALT_CN_SIGNIN};
+use crate::util::ds::{Clock, RandomSource, SystemClock, ThreadRandom};
+use crate::util::types::Region;
+
+
+/// How the client presents itself to HoYoLAB: DS salts, app versions and user agent.
+/// The defaults follow the official apps; override them when HoYoLAB rotates a value.
+#[derive(Debug, Clone)]
+pub struct ClientIdentity {
+    pub(crate) os_salt: String,
+    pub(crate) os_app_version: String,
+    pub(crate) cn_salt: String,
+    pub(crate) cn_app_version: String,
+    pub(crate) cn_signin_salt: String,
+    pub(crate) cn_signin_app_version: String,
+    pub(crate) user_agent: Option<String>,
+    pub(crate) clock: Arc<dyn Clock>,
+    pub(crate) rng: Arc<dyn RandomSource>,
+}
+
+impl Default for ClientIdentity {
+    fn default() -> Self {
+        ClientIdentity {
+            os_salt: DS_SALT[&Region::OVERSEAS].to_string(),
+            os_app_version: "1.5.0".to_string(),
+            cn_salt: DS_SALT[&Region::CHINESE].to_string(),
+            cn_app_version: "2.11.1".to_string(),
+            cn_signin_salt: DS_SALT_CN_SIGNIN.to_string(),
+            cn_signin_app_version: "2.34.1".to_string(),
+            user_agent: None,
+            clock: Arc::new(SystemClock),
+            rng: Arc::new(ThreadRandom),
+        }
+    }
+}
+
+impl ClientIdentity {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Salt and `x-rpc-app_version` of overseas requests.
+    pub fn overseas(mut self, salt: impl Into<String>, app_version: impl Into<String>) -> Self {
+        self.os_salt = salt.into();
+        self.os_app_version = app_version.into();
+        self
+    }
+
+    /// Salt and `x-rpc-app_version` of mainland requests signed with DS2.
+    pub fn chinese(mut self, salt: impl Into<String>, app_version: impl Into<String>) -> Self {
+        self.cn_salt = salt.into();
+        self.cn_app_version = app_version.into();
+        self
+    }
+
+    /// Salt and `x-rpc-app_version` of the mainland daily check-in.
+    pub fn chinese_signin(mut self, salt: impl Into<String>, app_version: impl Into<String>) -> Self {
+        self.cn_signin_salt = salt.into();
+        self.cn_signin_app_version = app_version.into();
+        self
+    }
+
+    /// Sent on every API request, in place of the one of the [`HttpConfig`](crate::client::HttpConfig).
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    pub fn rng(mut self, rng: impl RandomSource + 'static) -> Self {
+        self.rng = Arc::new(rng);
+        self
+    }
+}
@@ -0,0 +1,265 @@
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::util::error::{Error, Result};
+use crate::util::secret::Secret;
+
+
+/// Where each family of cookies is sent, as a domain and a path prefix.
+/// A host matches a domain and all of its subdomains.
+type Scope = (&'static str, &'static str);
+
+const LTOKEN_SCOPES: &[Scope] = &[("hoyolab.com", "/"), ("hoyoverse.com", "/"), ("mihoyo.com", "/")];
+const COOKIE_TOKEN_SCOPES: &[Scope] = &[
+    ("account.hoyoverse.com", "/"),
+    ("api-account-os.hoyolab.com", "/"),
+    ("sg-hk4e-api.hoyoverse.com", "/"),
+    ("sg-hkrpg-api.hoyoverse.com", "/"),
+    ("account.mihoyo.com", "/"),
+    ("api-takumi.mihoyo.com", "/"),
+];
+/// The stoken is the master token of the account, so takumi only gets it on its auth endpoints.
+const STOKEN_SCOPES: &[Scope] = &[
+    ("passport-api.hoyoverse.com", "/"),
+    ("passport-api.mihoyo.com", "/"),
+    ("api-os-takumi.mihoyo.com", "/auth/api/"),
+    ("api-os-takumi.mihoyo.com", "/account/auth/api/"),
+    ("api-takumi.mihoyo.com", "/auth/api/"),
+    ("api-takumi.mihoyo.com", "/account/auth/api/"),
+];
+
+/// The scopes of a family of cookies, and its cookies by name.
+type Family<'a> = (&'static [Scope], Vec<(&'static str, Option<&'a str>)>);
+
+
+/// The HoYoLAB / miHoYo credentials found in a cookie header. Unknown cookies are left out.
+/// Tokens are kept as [`Secret`]s, so printing the cookies never shows them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Cookies {
+    pub ltuid: Option<String>,
+    pub ltoken: Option<Secret>,
+    pub ltuid_v2: Option<String>,
+    pub ltoken_v2: Option<Secret>,
+    pub ltmid_v2: Option<String>,
+    pub cookie_token: Option<Secret>,
+    pub cookie_token_v2: Option<Secret>,
+    pub account_id: Option<String>,
+    pub account_id_v2: Option<String>,
+    pub stoken: Option<Secret>,
+    pub mid: Option<String>,
+    /// When the tokens stop being valid, when known. A header carries no expiry, so parsing leaves it unset.
+    pub expires_at: Option<SystemTime>,
+}
+
+impl Cookies {
+    /// Reads a `Cookie` header as copied from the browser, e.g. `"ltuid_v2=1000; ltoken_v2=v2_..."`.
+    pub fn parse(header: &str) -> Result<Cookies> {
+        let pairs = header.split(';')
+            .map(str::trim)
+            .filter(|pair| !pair.is_empty())
+            .enumerate()
+            // The pair itself is left out of the error, it may well be a token.
+            .map(|(index, pair)| pair.split_once('=')
+                .map(|(name, value)| (name.trim(), value.trim()))
+                .ok_or_else(|| Error::MalformedCookie(format!("cookie #{} is not a name=value pair", index + 1))))
+            .collect::<Result<Vec<_>>>()?;
+
+        let cookies = Cookies::from_pairs(pairs);
+        if cookies == Cookies::default() {
+            return Err(Error::MalformedCookie("no HoYoLAB credentials in the cookie header".to_string()));
+        }
+        Ok(cookies)
+    }
+
+    pub fn from_pairs<I, K, V>(pairs: I) -> Cookies
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: Into<String>,
+    {
+        let mut cookies = Cookies::default();
+        for (name, value) in pairs {
+            cookies.set(name.as_ref(), value.into());
+        }
+        cookies
+    }
+
+    /// Sets the cookie `name`; returns whether it is one of ours and its value changed.
+    pub(crate) fn set(&mut self, name: &str, value: String) -> bool {
+        fn replace<T: PartialEq>(slot: &mut Option<T>, value: T) -> bool {
+            let changed = slot.as_ref() != Some(&value);
+            *slot = Some(value);
+            changed
+        }
+
+        match name {
+            "ltuid" => replace(&mut self.ltuid, value),
+            "ltoken" => replace(&mut self.ltoken, value.into()),
+            "ltuid_v2" => replace(&mut self.ltuid_v2, value),
+            "ltoken_v2" => replace(&mut self.ltoken_v2, value.into()),
+            "ltmid_v2" => replace(&mut self.ltmid_v2, value),
+            "cookie_token" => replace(&mut self.cookie_token, value.into()),
+            "cookie_token_v2" => replace(&mut self.cookie_token_v2, value.into()),
+            "account_id" => replace(&mut self.account_id, value),
+            "account_id_v2" => replace(&mut self.account_id_v2, value),
+            "stoken" => replace(&mut self.stoken, value.into()),
+            "mid" => replace(&mut self.mid, value),
+            _ => false,
+        }
+    }
+
+    /// Merges the `Set-Cookie` headers of a response. Deletions are ignored, so a logout elsewhere
+    /// surfaces as [`Error::InvalidCookies`] rather than as missing cookies. Returns whether anything changed.
+    pub(crate) fn merge_set_cookies<'a>(&mut self, headers: impl IntoIterator<Item = &'a str>) -> bool {
+        let now = SystemTime::now();
+        let mut changed = false;
+        let mut expires_at = None::<SystemTime>;
+        for header in headers {
+            let Ok(cookie) = cookie::Cookie::parse(header) else {
+                continue;
+            };
+            let max_age = cookie.max_age().map(|max_age| max_age.whole_seconds());
+            if cookie.value().is_empty() || max_age.is_some_and(|seconds| seconds <= 0) {
+                continue;
+            }
+            if self.set(cookie.name(), cookie.value().to_string()) {
+                changed = true;
+                let expiry = match max_age {
+                    Some(seconds) => Some(now + Duration::from_secs(seconds.unsigned_abs())),
+                    None => cookie.expires_datetime().map(SystemTime::from),
+                };
+                // The first refreshed token to expire bounds the validity of the set.
+                if let Some(expiry) = expiry {
+                    expires_at = Some(expires_at.map_or(expiry, |current| current.min(expiry)));
+                }
+            }
+        }
+        if expires_at.is_some() {
+            self.expires_at = expires_at;
+        }
+        changed
+    }
+
+    /// The HoYoLAB account the cookies belong to.
+    pub fn account_id(&self) -> Option<&str> {
+        [&self.ltuid, &self.ltuid_v2, &self.account_id_v2, &self.account_id]
+            .into_iter()
+            .find_map(Option::as_deref)
+    }
+
+    fn families(&self) -> [Family<'_>; 3] {
+        [
+            (LTOKEN_SCOPES, vec![
+                ("ltuid", self.ltuid.as_deref()),
+                ("ltoken", self.ltoken.as_ref().map(Secret::expose)),
+                ("ltuid_v2", self.ltuid_v2.as_deref()),
+                ("ltoken_v2", self.ltoken_v2.as_ref().map(Secret::expose)),
+                ("ltmid_v2", self.ltmid_v2.as_deref()),
+            ]),
+            (COOKIE_TOKEN_SCOPES, vec![
+                ("account_id", self.account_id.as_deref()),
+                ("cookie_token", self.cookie_token.as_ref().map(Secret::expose)),
+                ("account_id_v2", self.account_id_v2.as_deref()),
+                ("cookie_token_v2", self.cookie_token_v2.as_ref().map(Secret::expose)),
+            ]),
+            (STOKEN_SCOPES, vec![
+                ("stoken", self.stoken.as_ref().map(Secret::expose)),
+                ("mid", self.mid.as_deref()),
+            ]),
+        ]
+    }
+
+    /// The `Cookie` header for a request to `path` on `host`, holding only the cookies meant for it.
+    pub(crate) fn header_for(&self, host: &str, path: &str) -> Option<String> {
+        let families = self.families().into_iter()
+            .filter(|(scopes, _)| scopes.iter().any(|(domain, prefix)| matches_domain(host, domain) && path.starts_with(prefix)));
+        let header = join(families);
+        (!header.is_empty()).then_some(header)
+    }
+
+    /// Every cookie as one header, the way [`parse`](Self::parse) reads it back. Tokens are exposed.
+    pub(crate) fn to_header(&self) -> String {
+        join(self.families())
+    }
+}
+
+fn join<'a>(families: impl IntoIterator<Item = Family<'a>>) -> String {
+    families.into_iter()
+        .flat_map(|(_, cookies)| cookies.into_iter())
+        .filter_map(|(name, value)| value.map(|value| format!("{}={}", name, value)))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Stored as a single `Cookie` header string, or next to its expiry in seconds since the epoch when that is known.
+impl Serialize for Cookies {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let header = self.to_header();
+        match self.expires_at {
+            Some(expires_at) => {
+                let expires_at = expires_at.duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default().as_secs();
+                StoredCookies::WithExpiry { header, expires_at }.serialize(serializer)
+            }
+            None => StoredCookies::Header(header).serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Cookies {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Cookies, D::Error> {
+        let (header, expires_at) = match StoredCookies::deserialize(deserializer)? {
+            StoredCookies::Header(header) => (header, None),
+            StoredCookies::WithExpiry { header, expires_at } => (header, Some(expires_at)),
+        };
+        let mut cookies = Cookies::parse(&header).map_err(serde::de::Error::custom)?;
+        cookies.expires_at = expires_at.map(|secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs));
+        Ok(cookies)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum StoredCookies {
+    Header(String),
+    WithExpiry { header: String, expires_at: u64 },
+}
+
+impl FromStr for Cookies {
+    type Err = Error;
+
+    fn from_str(header: &str) -> Result<Cookies> {
+        Cookies::parse(header)
+    }
+}
+
+fn matches_domain(host: &str, domain: &str) -> bool {
+    host == domain || host.strip_suffix(domain).is_some_and(|sub| sub.ends_with('.'))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_scopes_a_browser_header() {
+        let cookies = Cookies::parse(
+            "_MHYUUID=5e1c; ltuid_v2=1000; ltoken_v2=v2_lt; ltmid_v2=mid_lt; account_id_v2=1000; cookie_token_v2=v2_ct; stoken=v2_st; mid=mid_st"
+        ).unwrap();
+        assert_eq!(cookies.account_id(), Some("1000"));
+
+        assert_eq!(cookies.header_for("bbs-api-os.hoyolab.com", "/").unwrap(), "ltuid_v2=1000; ltoken_v2=v2_lt; ltmid_v2=mid_lt");
+        assert_eq!(
+            cookies.header_for("sg-hkrpg-api.hoyoverse.com", "/").unwrap(),
+            "ltuid_v2=1000; ltoken_v2=v2_lt; ltmid_v2=mid_lt; account_id_v2=1000; cookie_token_v2=v2_ct"
+        );
+        assert!(cookies.header_for("api-takumi.mihoyo.com", "/auth/api/getMultiTokenByLoginTicket").unwrap().ends_with("stoken=v2_st; mid=mid_st"));
+        assert!(!cookies.header_for("api-takumi.mihoyo.com", "/binding/api/getUserGameRolesByCookie").unwrap().contains("stoken"));
+        assert!(cookies.header_for("passport-api.hoyoverse.com", "/account/ma-passport/token/verifySToken").unwrap().contains("stoken"));
+        assert_eq!(cookies.header_for("example.com", "/"), None);
+        assert_eq!(cookies.header_for("nothoyolab.com", "/"), None);
+
+        assert!(matches!(Cookies::parse("theme=dark"), Err(Error::MalformedCookie(_))));
+        assert!(matches!(Cookies::parse("ltuid"), Err(Error::MalformedCookie(_))));
+    }
+}
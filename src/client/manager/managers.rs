@@ -1,84 +1,311 @@
-use crate::util::types::{
-    AnyCookieOrHeader,
-    CookieOrHeader,
-};
-
-
-//  I will WRITE someday
-// pub(crate) fn parse_cookie<'a>(cookie: Option<CookieOrHeader>) -> NaturalDict<'a> {
-//     let mut cookies = NaturalDict::new();
-//
-//     if cookie.is_none() {
-//         return cookies;
-//     }
-//
-//     if let CookieOrHeader::Str(cookie) =  cookie.as_ref().unwrap() {
-//         cookies = _parse_cookie(cookie);
-//     }
-//
-//     for (k,v) in cookies {
-//
-//     }
-//
-//     cookies
-// }
-
-// fn _parse_cookie(cookie: &str) -> NaturalDict {
-//     let mut dict = NaturalDict::new();
-//     let material = cookie.split(",").collect::<Vec<String>>();
-//
-//     for i in material {
-//         if i.contains("=") {
-//             let x = i.split("=").collect();
-//             dict.insert(x.0,x.1);
-//         }
-//     }
-//
-//     dict
-// }
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use crate::client::http::ProxyConfig;
+use crate::client::retry::RetryClass;
+use crate::client::manager::cookie::Cookies;
+use crate::util::error::{Error, Result};
+use crate::util::types::CookieOrHeader;
 
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) struct BaseCookieManager {
-    cookies: Option<CookieOrHeader>
+    cookies: Option<Cookies>,
+    proxy: Option<ProxyConfig>,
 }
 
 impl BaseCookieManager {
     pub(crate) fn new(cookie: Option<CookieOrHeader>) -> BaseCookieManager {
-        BaseCookieManager { cookies: cookie }
+        let cookies = cookie.map(|cookie| match cookie {
+            CookieOrHeader::Dict(dict) => Cookies::from_pairs(dict),
+        });
+        BaseCookieManager::from_parsed(cookies)
+    }
+
+    pub(crate) fn from_parsed(cookies: Option<Cookies>) -> BaseCookieManager {
+        BaseCookieManager { cookies, proxy: None }
+    }
+
+    pub(crate) fn get_cookies(&self) -> Option<&Cookies> {
+        self.cookies.as_ref()
+    }
+
+    pub(crate) fn get_cookies_mut(&mut self) -> Option<&mut Cookies> {
+        self.cookies.as_mut()
+    }
+
+    pub(crate) fn get_proxy(&self) -> Option<&ProxyConfig> {
+        self.proxy.as_ref()
     }
 
-    pub(crate) fn from_cookies(cookies: Option<AnyCookieOrHeader>) -> BaseCookieManager {
-        if cookies.is_none() {
-            return BaseCookieManager { cookies: None };
+    pub(crate) fn set_proxy(&mut self, proxy: Option<ProxyConfig>) {
+        self.proxy = proxy;
+    }
+
+    /// The HoYoLAB account the cookies belong to.
+    pub(crate) fn account_id(&self) -> Option<&str> {
+        self.cookies.as_ref()?.account_id()
+    }
+
+    /// The `Cookie` header for a request to `path` on `host`.
+    pub(crate) fn cookie_header(&self, host: &str, path: &str) -> Result<String> {
+        self.cookies.as_ref()
+            .and_then(|cookies| cookies.header_for(host, path))
+            .ok_or(Error::NotConfigured("cookies"))
+    }
+}
+
+
+/// A credential of the cookie pool, with the proxy its requests egress through, if any.
+/// Plain [`Cookies`] convert into one without a proxy.
+#[derive(Debug, Clone)]
+pub struct PoolEntry {
+    pub(crate) cookies: Cookies,
+    pub(crate) proxy: Option<ProxyConfig>,
+}
+
+impl PoolEntry {
+    pub fn new(cookies: Cookies) -> Self {
+        PoolEntry { cookies, proxy: None }
+    }
+
+    /// Routes every request made with this credential through `proxy`, like [`Client::set_account_proxy`](crate::client::Client::set_account_proxy).
+    pub fn proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+}
+
+impl From<Cookies> for PoolEntry {
+    fn from(cookies: Cookies) -> Self {
+        PoolEntry::new(cookies)
+    }
+}
+
+impl From<PoolEntry> for BaseCookieManager {
+    fn from(entry: PoolEntry) -> Self {
+        BaseCookieManager { cookies: Some(entry.cookies), proxy: entry.proxy }
+    }
+}
+
+
+/// The credentials of a [`Client`](crate::client::Client), shared by its base client, every game component
+/// and every clone, so a refresh or an account switch takes effect everywhere at once.
+#[derive(Debug, Default)]
+pub(crate) struct CredentialStore {
+    current: RwLock<Option<Arc<BaseCookieManager>>>,
+}
+
+impl CredentialStore {
+    pub(crate) fn with(manager: Arc<BaseCookieManager>) -> CredentialStore {
+        CredentialStore { current: RwLock::new(Some(manager)) }
+    }
+
+    /// A snapshot of the current credentials; later changes to the store don't affect it.
+    pub(crate) fn get(&self) -> Option<Arc<BaseCookieManager>> {
+        self.current.read().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+    }
+
+    pub(crate) fn set(&self, manager: Option<BaseCookieManager>) {
+        *self.current.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = manager.map(Arc::new);
+    }
+
+    /// Merges the `Set-Cookie` headers of a response to a request sent with `sent_with`, unless the store
+    /// has switched to another account since. Returns the cookies when something changed.
+    pub(crate) fn refresh<'a>(&self, sent_with: &BaseCookieManager, headers: impl IntoIterator<Item = &'a str>) -> Option<Cookies> {
+        let mut current = self.current.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let manager = current.as_mut()?;
+        if manager.account_id() != sent_with.account_id() {
+            return None;
+        }
+        let mut updated = BaseCookieManager::clone(manager);
+        let cookies = updated.get_cookies_mut()?;
+        if !cookies.merge_set_cookies(headers) {
+            return None;
         }
+        let cookies = cookies.clone();
+        *manager = Arc::new(updated);
+        Some(cookies)
+    }
 
-        match cookies.unwrap() {
-            AnyCookieOrHeader::CookieOrHeader(any_cookie) => {
-                return match any_cookie {
-                    CookieOrHeader::Dict(dict) => {
-                        BaseCookieManager::new(Some(CookieOrHeader::Dict(dict)))
-                    }
-                }
-            }
-            _ => {
-            }
+    /// Changes the current credentials in place.
+    pub(crate) fn update(&self, apply: impl FnOnce(&mut BaseCookieManager)) -> Result<()> {
+        let mut current = self.current.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let manager = current.as_mut().ok_or(Error::NotConfigured("cookies"))?;
+        apply(Arc::make_mut(manager));
+        Ok(())
+    }
+}
+
+
+/// Called with the credentials after HoYoLAB refreshed some of them through `Set-Cookie`.
+#[derive(Clone)]
+pub(crate) struct CookieListener(pub(crate) Arc<dyn Fn(&Cookies) + Send + Sync>);
+
+impl std::fmt::Debug for CookieListener {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("CookieListener")
+    }
+}
+
+
+/// How a credential of the cookie pool has fared so far.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CredentialStats {
+    pub account_id: Option<String>,
+    pub requests: u64,
+    pub successes: u64,
+    pub rate_limited: u64,
+    /// Failures other than rate limits and rejections.
+    pub failures: u64,
+    /// Set while the credential rests after hitting the rate limit.
+    pub cooldown_until: Option<Instant>,
+    /// HoYoLAB rejected the credential; it is never used again.
+    pub invalid: bool,
+}
+
+#[derive(Debug)]
+struct PooledCredential {
+    manager: Arc<BaseCookieManager>,
+    stats: CredentialStats,
+}
+
+impl PooledCredential {
+    fn is_available(&self, now: Instant) -> bool {
+        !self.stats.invalid && self.stats.cooldown_until.is_none_or(|until| until <= now)
+    }
+}
+
+#[derive(Debug)]
+struct Rotation {
+    pool: Vec<PooledCredential>,
+    cooldown: Duration,
+}
+
+/// A pool of credentials read-only requests are spread over, least used first.
+/// Shared like the [`CredentialStore`], so every component and clone draws from the same pool.
+#[derive(Debug)]
+pub(crate) struct RotatingCookieManager {
+    rotation: Mutex<Rotation>,
+}
+
+impl Default for RotatingCookieManager {
+    fn default() -> Self {
+        RotatingCookieManager {
+            rotation: Mutex::new(Rotation { pool: Vec::new(), cooldown: Duration::from_secs(60) }),
         }
-        BaseCookieManager::new(None)
     }
+}
 
-    pub(crate) fn forming_cookie(&self) -> (String, String) {
-        let header = &self.cookies.as_ref().unwrap();
+impl RotatingCookieManager {
+    fn lock(&self) -> std::sync::MutexGuard<'_, Rotation> {
+        self.rotation.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
 
-        match header {
-            CookieOrHeader::Dict(cookie) => {
-                let ltuid = cookie.get("ltuid").unwrap();
-                let ltoken = cookie.get("ltoken").unwrap();
-                return (format!("ltuid={}", ltuid), format!("ltoken={}", ltoken))
+    /// Replaces the pool; the statistics start over.
+    pub(crate) fn set(&self, managers: Vec<BaseCookieManager>) {
+        self.lock().pool = managers.into_iter()
+            .map(|manager| PooledCredential {
+                stats: CredentialStats {
+                    account_id: manager.account_id().map(str::to_string),
+                    requests: 0,
+                    successes: 0,
+                    rate_limited: 0,
+                    failures: 0,
+                    cooldown_until: None,
+                    invalid: false,
+                },
+                manager: Arc::new(manager),
+            })
+            .collect();
+    }
+
+    /// How long a credential rests after hitting the rate limit.
+    pub(crate) fn set_cooldown(&self, cooldown: Duration) {
+        self.lock().cooldown = cooldown;
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.lock().pool.is_empty()
+    }
+
+    /// The least used credential that is neither cooling down nor rejected.
+    pub(crate) fn acquire(&self) -> Option<Arc<BaseCookieManager>> {
+        let now = Instant::now();
+        let mut rotation = self.lock();
+        let credential = rotation.pool.iter_mut()
+            .filter(|credential| credential.is_available(now))
+            .min_by_key(|credential| credential.stats.requests)?;
+        credential.stats.requests += 1;
+        credential.stats.cooldown_until = None;
+        Some(credential.manager.clone())
+    }
+
+    /// Records the outcome of a request sent with `manager`.
+    pub(crate) fn report<T>(&self, manager: &Arc<BaseCookieManager>, result: &Result<T>) {
+        let mut rotation = self.lock();
+        let cooldown = rotation.cooldown;
+        let Some(credential) = rotation.pool.iter_mut().find(|credential| Arc::ptr_eq(&credential.manager, manager)) else {
+            return;
+        };
+        let stats = &mut credential.stats;
+        match result {
+            Ok(_) => stats.successes += 1,
+            Err(Error::InvalidCookies { .. } | Error::ExpiredCookies { .. }) => {
+                tracing::warn!(account_id = ?stats.account_id, "dropping a rejected credential from the cookie pool");
+                stats.invalid = true;
+            }
+            Err(why) if RetryClass::of(why) == Some(RetryClass::RateLimited) => {
+                stats.rate_limited += 1;
+                stats.cooldown_until = Some(Instant::now() + cooldown);
             }
-            _ => (String::new(), String::new())
+            Err(_) => stats.failures += 1,
+        }
+    }
+
+    /// Swaps `old` for `new`, e.g. after HoYoLAB refreshed its tokens; the statistics are kept.
+    pub(crate) fn replace(&self, old: &Arc<BaseCookieManager>, new: Arc<BaseCookieManager>) {
+        let mut rotation = self.lock();
+        if let Some(credential) = rotation.pool.iter_mut().find(|credential| Arc::ptr_eq(&credential.manager, old)) {
+            credential.manager = new;
         }
     }
 
+    pub(crate) fn stats(&self) -> Vec<CredentialStats> {
+        self.lock().pool.iter().map(|credential| credential.stats.clone()).collect()
+    }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn credential(ltuid: &str) -> BaseCookieManager {
+        BaseCookieManager::from_parsed(Some(Cookies::from_pairs([("ltuid", ltuid), ("ltoken", "secret")])))
+    }
+
+    #[test]
+    fn rotation_rests_rate_limited_and_drops_rejected_credentials() {
+        let rotation = RotatingCookieManager::default();
+        rotation.set(vec![credential("1"), credential("2"), credential("3")]);
+
+        let first = rotation.acquire().unwrap();
+        rotation.report(&first, &Err::<(), _>(Error::TooManyRequests { retcode: 10101, message: String::new() }));
+        let second = rotation.acquire().unwrap();
+        rotation.report(&second, &Err::<(), _>(Error::InvalidCookies { retcode: -100, message: String::new() }));
+        let third = rotation.acquire().unwrap();
+        rotation.report(&third, &Ok(()));
+        assert_eq!(third.account_id(), Some("3"));
+
+        // Only the third is left until the first has rested.
+        assert!(Arc::ptr_eq(&rotation.acquire().unwrap(), &third));
+        rotation.set_cooldown(Duration::ZERO);
+        rotation.report(&first, &Err::<(), _>(Error::Status(reqwest::StatusCode::TOO_MANY_REQUESTS)));
+        assert!(Arc::ptr_eq(&rotation.acquire().unwrap(), &first));
+
+        let stats = rotation.stats();
+        assert_eq!((stats[0].requests, stats[0].rate_limited), (2, 2));
+        assert!(stats[1].invalid);
+        assert_eq!((stats[2].requests, stats[2].successes), (2, 1));
+    }
+}
@@ -1,5 +1,36 @@
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub(crate) mod builder;
 pub(crate) mod cache;
+pub(crate) mod captcha;
+pub(crate) mod cassette;
+#[allow(clippy::module_inception)]
 pub(crate) mod client;
 pub(crate) mod component;
+pub(crate) mod device;
+pub(crate) mod endpoint;
+pub(crate) mod http;
+pub(crate) mod identity;
 pub(crate) mod manager;
+pub(crate) mod profile;
+pub(crate) mod retry;
 pub(crate) mod routes;
+pub(crate) mod transport;
+#[cfg(feature = "vault")]
+pub(crate) mod vault;
+
+pub use self::builder::ClientBuilder;
+pub use self::captcha::{CaptchaChallenge, CaptchaSolution, CaptchaSolver};
+pub use self::cassette::{CassettePlayer, CassetteRecorder};
+pub use self::client::Client;
+pub use self::device::DeviceConfig;
+pub use self::endpoint::{BaseRoute, Endpoint};
+pub use self::http::{HttpConfig, ProxyConfig};
+pub use self::identity::ClientIdentity;
+pub use self::manager::cookie::Cookies;
+pub use self::manager::managers::{CredentialStats, PoolEntry};
+pub use self::profile::{Profile, Profiles};
+pub use self::retry::{RetryClass, RetryPolicy};
+pub use self::transport::{HttpRequest, HttpResponse, Transport};
+#[cfg(feature = "vault")]
+pub use self::vault::{KdfParams, Vault};
@@ -0,0 +1,150 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::client::manager::cookie::Cookies;
+use crate::util::error::{Error, Result};
+use crate::util::types::{Game, Region};
+
+
+/// The credentials of one account, with the defaults a client is built with for it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    /// Stored as a `Cookie` header, e.g. `"ltuid_v2=1000; ltoken_v2=v2_..."`.
+    pub cookies: Cookies,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<Region>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub uids: BTreeMap<Game, u32>,
+}
+
+impl Profile {
+    pub fn new(cookies: Cookies) -> Self {
+        Profile { cookies, region: None, lang: None, uids: BTreeMap::new() }
+    }
+}
+
+
+/// Named [`Profile`]s kept in one file. Files ending in `.toml` are TOML, any other is JSON.
+///
+/// ```toml
+/// [profiles.main]
+/// cookies = "ltuid_v2=1000; ltoken_v2=v2_..."
+/// region = "overseas"
+/// lang = "ja-jp"
+///
+/// [profiles.main.uids]
+/// starrail = 800000002
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Profiles {
+    #[serde(default)]
+    profiles: BTreeMap<String, Profile>,
+}
+
+impl Profiles {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Profiles> {
+        let path = path.as_ref();
+        let invalid = |reason: String| Error::Config { path: path.to_path_buf(), reason };
+        let contents = std::fs::read_to_string(path).map_err(|why| invalid(why.to_string()))?;
+        if is_toml(path) {
+            toml::from_str(&contents).map_err(|why| invalid(why.to_string()))
+        } else {
+            serde_json::from_str(&contents).map_err(|why| invalid(why.to_string()))
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let invalid = |reason: String| Error::Config { path: path.to_path_buf(), reason };
+        let contents = if is_toml(path) {
+            toml::to_string_pretty(self).map_err(|why| invalid(why.to_string()))?
+        } else {
+            serde_json::to_string_pretty(self).map_err(|why| invalid(why.to_string()))?
+        };
+        // Written aside and renamed over, like the vault, so a crash never leaves half a file.
+        let mut partial = path.as_os_str().to_owned();
+        partial.push(".partial");
+        write_private(Path::new(&partial), contents.as_bytes())
+            .and_then(|_| std::fs::rename(&partial, path))
+            .map_err(|why| invalid(why.to_string()))
+    }
+
+    pub fn get(&self, name: &str) -> Result<&Profile> {
+        self.profiles.get(name).ok_or_else(|| Error::ProfileNotFound(name.to_string()))
+    }
+
+    /// Adds `profile`, replacing the one of the same name.
+    pub fn insert(&mut self, name: impl Into<String>, profile: Profile) {
+        self.profiles.insert(name.into(), profile);
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Profile> {
+        self.profiles.remove(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.profiles.keys().map(String::as_str)
+    }
+}
+
+/// Profiles hold tokens in plain text, so on unix only the owner may read the file.
+fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(contents)
+}
+
+fn is_toml(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("toml"))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_profiles_through_toml_and_json() {
+        let mut profile = Profile::new(Cookies::parse("ltuid_v2=1000; ltoken_v2=v2_lt; cookie_token_v2=v2_ct").unwrap());
+        profile.region = Some(Region::OVERSEAS);
+        profile.uids.insert(Game::STARRAIL, 800000002);
+        let mut profiles = Profiles::new();
+        profiles.insert("main", profile.clone());
+        profile.cookies.expires_at = Some(std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_900_000_000));
+        profiles.insert("refreshed", profile);
+
+        let dir = std::env::temp_dir().join(format!("mihoyo-profiles-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for file in ["profiles.toml", "profiles.json"] {
+            profiles.save(dir.join(file)).unwrap();
+            assert_eq!(Profiles::load(dir.join(file)).unwrap(), profiles);
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(dir.join("profiles.toml")).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        let toml = std::fs::read_to_string(dir.join("profiles.toml")).unwrap();
+        assert!(toml.contains(r#"region = "overseas""#));
+
+        assert!(matches!(profiles.get("alt"), Err(Error::ProfileNotFound(_))));
+        assert!(matches!(Profiles::load(dir.join("missing.toml")), Err(Error::Config { .. })));
+        std::fs::write(dir.join("broken.json"), r#"{"profiles":{"main":{"cookies":"theme=dark"}}}"#).unwrap();
+        let Err(Error::Config { reason, .. }) = Profiles::load(dir.join("broken.json")) else { panic!() };
+        assert!(reason.contains("no HoYoLAB credentials"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,158 @@
+use std::io::ErrorKind;
+use std::time::Duration;
+use rand::Rng;
+use crate::util::error::Error;
+
+
+/// Kinds of failures a [`RetryPolicy`] can retry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RetryClass {
+    /// Retcode 10101/-110 or HTTP 429.
+    RateLimited,
+    /// HTTP 5xx without a readable envelope.
+    ServerError,
+    /// Connection failures, resets and timeouts.
+    Connection,
+}
+
+impl RetryClass {
+    pub(crate) fn of(error: &Error) -> Option<RetryClass> {
+        match error {
+            Error::TooManyRequests { .. } => Some(RetryClass::RateLimited),
+            Error::Status(status) if status.as_u16() == 429 => Some(RetryClass::RateLimited),
+            Error::Status(status) if status.is_server_error() => Some(RetryClass::ServerError),
+            Error::Transport(why) if why.is_connect() || why.is_timeout() || why.is_request() || why.is_body() => {
+                Some(RetryClass::Connection)
+            }
+            Error::Io(why) if matches!(
+                why.kind(),
+                ErrorKind::ConnectionReset | ErrorKind::ConnectionAborted | ErrorKind::ConnectionRefused | ErrorKind::TimedOut
+            ) => Some(RetryClass::Connection),
+            _ => None,
+        }
+    }
+}
+
+
+/// How often and how patiently a failed request is attempted again.
+/// Delays grow exponentially from `base_delay` up to `max_delay`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    pub(crate) max_attempts: u32,
+    pub(crate) base_delay: Duration,
+    pub(crate) max_delay: Duration,
+    pub(crate) jitter: bool,
+    pub(crate) retry_on: Vec<RetryClass>,
+    pub(crate) retry_writes: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(8),
+            jitter: true,
+            retry_on: vec![RetryClass::RateLimited, RetryClass::ServerError, RetryClass::Connection],
+            retry_writes: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A policy that sends every request exactly once.
+    pub fn none() -> Self {
+        Self::default().max_attempts(1)
+    }
+
+    /// Total number of attempts, including the first one.
+    pub fn max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    pub fn backoff(mut self, base_delay: Duration, max_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self.max_delay = max_delay.max(base_delay);
+        self
+    }
+
+    /// Randomizes each delay between half and all of its exponential value.
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    pub fn retry_on<I: IntoIterator<Item = RetryClass>>(mut self, classes: I) -> Self {
+        self.retry_on = classes.into_iter().collect();
+        self
+    }
+
+    /// Also retries requests other than `GET` on server errors and connection failures.
+    /// Off by default: such a request may have gone through before failing, e.g. a check-in.
+    /// Rate limited requests were rejected outright and are retried whatever the method.
+    pub fn retry_writes(mut self, retry_writes: bool) -> Self {
+        self.retry_writes = retry_writes;
+        self
+    }
+
+    /// The same policy, but never retrying `class`.
+    pub(crate) fn without(&self, class: RetryClass) -> RetryPolicy {
+        let mut policy = self.clone();
+        policy.retry_on.retain(|retry_on| *retry_on != class);
+        policy
+    }
+
+    /// Whether to try again after `error`; `idempotent` tells whether sending the request twice is harmless.
+    pub(crate) fn should_retry(&self, error: &Error, attempt: u32, idempotent: bool) -> bool {
+        attempt < self.max_attempts
+            && RetryClass::of(error).is_some_and(|class| {
+                self.retry_on.contains(&class) && (idempotent || self.retry_writes || class == RetryClass::RateLimited)
+            })
+    }
+
+    /// The pause before attempt `attempt + 1`.
+    pub(crate) fn delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        let delay = self.base_delay.saturating_mul(1 << exponent).min(self.max_delay);
+        if !self.jitter || delay.is_zero() {
+            return delay;
+        }
+        let half = delay / 2;
+        half + rand::thread_rng().gen_range(Duration::ZERO..=half)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_is_capped_and_classes_are_respected() {
+        let policy = RetryPolicy::new()
+            .max_attempts(4)
+            .backoff(Duration::from_millis(100), Duration::from_millis(250))
+            .jitter(false)
+            .retry_on([RetryClass::RateLimited]);
+
+        assert_eq!(policy.delay(1), Duration::from_millis(100));
+        assert_eq!(policy.delay(2), Duration::from_millis(200));
+        assert_eq!(policy.delay(3), Duration::from_millis(250));
+
+        let limited = Error::from_retcode(10101, String::new());
+        assert!(policy.should_retry(&limited, 3, true));
+        assert!(!policy.should_retry(&limited, 4, true));
+        assert!(!policy.should_retry(&Error::Status(reqwest::StatusCode::BAD_GATEWAY), 1, true));
+
+        let bad_gateway = Error::Status(reqwest::StatusCode::BAD_GATEWAY);
+        assert!(RetryPolicy::new().should_retry(&bad_gateway, 1, true));
+        assert!(!RetryPolicy::new().should_retry(&bad_gateway, 1, false));
+        assert!(RetryPolicy::new().should_retry(&limited, 1, false));
+        assert!(RetryPolicy::new().retry_writes(true).should_retry(&bad_gateway, 1, false));
+    }
+}
@@ -1,6 +1,6 @@
 use std::collections::HashMap;
+use crate::util::error::{Error, Result};
 use crate::util::types::{
-    GeneralResult,
  Region,
  Game,
 
@@ -11,43 +11,52 @@ type Dict<'a,
  &'a str>;
 
 
+#[allow(clippy::new_ret_no_self)]
 pub(crate) trait RouteTrait<'a> {
-    fn new(url: &'a str) -> Route {
+    fn new(url: &'a str) -> Route<'a> {
         Route(url)
     }
-    fn get_url(&self) -> GeneralResult<&'_ str>;
+    // Static routes are declared, but no endpoint requests one yet.
+    #[allow(dead_code)]
+    fn get_url(&self) -> Result<&'_ str>;
 }
 
+#[allow(clippy::new_ret_no_self)]
 pub(crate) trait InternationalTrait<'a> {
     fn new(overseas: &'a str,
  chinese: &'a str) -> InternationalRoute<'a>;
     fn get_url(&self,
- region: Region) ->  GeneralResult<&'_ str>;
+ region: Region) ->  Result<&'_ str>;
 }
 
+#[allow(clippy::new_ret_no_self)]
 pub(crate) trait GameTrait<'a> {
     fn new(overseas: Option<&'a[(Game,
  &'a str)]>,
  chinese: Option<&'a[(Game,
  &'a str)]>) -> GameRoute<'a>;
+    // Per game routes are declared, but no endpoint requests one yet.
+    #[allow(dead_code)]
     fn get_url(&self,
  region: Region,
- game: Game) ->  GeneralResult<&'_ str>;
+ game: Game) ->  Result<&'_ str>;
 }
 
 
+#[allow(dead_code)]
 pub(crate) struct Route<'a>(&'a str);
 
 pub(crate) struct InternationalRoute<'a>(Dict<'a,
  Region>);
 
+#[allow(dead_code)]
 pub(crate) struct GameRoute<'a>(HashMap<&'a Region,
  Dict<'a,
  Game>>);
 
 
 impl RouteTrait<'_> for Route<'_> {
-    fn get_url(&self) -> GeneralResult<&'_ str> {
+    fn get_url(&self) -> Result<&'_ str> {
         Ok(self.0)
     }
 }
@@ -64,12 +73,11 @@ impl InternationalTrait<'_> for InternationalRoute<'_> {
     }
 
     fn get_url(&self,
- region: Region) -> GeneralResult<&'_ str> {
-        if self.0.get(&region).is_none() {
-            return Err(Box::try_from(format!("URL does not support `{}` name",
- region.name())).unwrap());
-        }
-        Ok(self.0.get(&region).unwrap())
+ region: Region) -> Result<&'_ str> {
+        self.0.get(&region)
+            .copied()
+            .ok_or_else(|| Error::Route(format!("URL does not support `{}` name",
+ region.name())))
     }
 }
 
@@ -109,15 +117,13 @@ impl GameTrait<'_> for GameRoute<'_> {
 
     fn get_url(&self,
  region: Region,
- game: Game) -> GeneralResult<&'_ str> {
-        if self.0.get(&region).is_none() {
-            return Err(Box::try_from(format!("URL does not support {}",
- region.name())).unwrap());
-        };
-        if self.0.get(&region).unwrap().get(&game).is_none() {
-            return Err(Box::try_from(format!("URL does not support {}",
- game.name())).unwrap());
-        }
-        Ok(self.0.get(&region).unwrap().get(&game).unwrap())
+ game: Game) -> Result<&'_ str> {
+        let routes = self.0.get(&region)
+            .ok_or_else(|| Error::Route(format!("URL does not support {}",
+ region.name())))?;
+        routes.get(&game)
+            .copied()
+            .ok_or_else(|| Error::Route(format!("URL does not support {}",
+ game.name())))
     }
 }
@@ -0,0 +1,39 @@
+use std::fmt::Debug;
+use async_trait::async_trait;
+use reqwest::header::HeaderMap;
+use reqwest::{Method, StatusCode, Url};
+use crate::client::http::ProxyConfig;
+use crate::util::error::Result;
+
+
+/// A fully prepared request: the url already carries its query and the headers hold cookies and DS.
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    pub method: Method,
+    pub url: Url,
+    pub headers: HeaderMap,
+    pub body: Option<Vec<u8>>,
+    /// The proxy the request should egress through, if the client or account has one.
+    pub proxy: Option<ProxyConfig>,
+}
+
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    pub fn new(status: StatusCode, body: impl Into<Vec<u8>>) -> Self {
+        HttpResponse { status, headers: HeaderMap::new(), body: body.into() }
+    }
+}
+
+
+/// Sends requests on behalf of a [`Client`](crate::client::Client).
+/// The default implementation is backed by reqwest; tests can inject an in-memory one.
+#[async_trait]
+pub trait Transport: Debug + Send + Sync {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse>;
+}
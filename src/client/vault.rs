@@ -0,0 +1,273 @@
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chacha20poly1305::{Key, KeyInit, XChaCha20Poly1305, XNonce};
+use chacha20poly1305::aead::{Aead, Payload};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+use crate::client::manager::cookie::Cookies;
+use crate::util::error::{Error, Result};
+
+
+const VERSION: u32 = 1;
+/// Binds the wrapped data key to this format.
+const KEY_AAD: &[u8] = b"mihoyo-vault-v1";
+
+
+/// Cost of deriving the key from the passphrase with Argon2id. The defaults follow the OWASP recommendation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        KdfParams {
+            memory_kib: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Sealed {
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct VaultFile {
+    version: u32,
+    kdf: KdfParams,
+    salt: String,
+    /// The data key, sealed with the key derived from the passphrase.
+    key: Sealed,
+    /// Every entry is sealed on its own with the data key, its name as associated data.
+    entries: BTreeMap<String, Sealed>,
+}
+
+
+/// Credentials kept in a passphrase protected file.
+///
+/// Entries are encrypted one by one with XChaCha20-Poly1305 under a random data key, which is itself encrypted
+/// under a key derived from the passphrase. Only the data key stays in memory while the vault is open;
+/// an entry is decrypted when it is read and every change is written to disk at once.
+pub struct Vault {
+    path: PathBuf,
+    file: VaultFile,
+    key: Zeroizing<[u8; 32]>,
+}
+
+/// Leaves the data key out.
+impl std::fmt::Debug for Vault {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Vault")
+            .field("path", &self.path)
+            .field("entries", &self.file.entries.keys().collect::<Vec<_>>())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Vault {
+    /// Creates an empty vault at `path`; fails when the file already exists.
+    pub fn create(path: impl AsRef<Path>, passphrase: &str) -> Result<Vault> {
+        Vault::create_with(path, passphrase, KdfParams::default())
+    }
+
+    pub fn create_with(path: impl AsRef<Path>, passphrase: &str, kdf: KdfParams) -> Result<Vault> {
+        let key = Zeroizing::new(random_bytes::<32>());
+        let salt = random_bytes::<16>();
+        let wrapping_key = derive_key(passphrase, &salt, kdf)?;
+        let file = VaultFile {
+            version: VERSION,
+            kdf,
+            salt: STANDARD.encode(salt),
+            key: seal(&wrapping_key, KEY_AAD, key.as_slice())?,
+            entries: BTreeMap::new(),
+        };
+        let vault = Vault { path: path.as_ref().to_path_buf(), file, key };
+
+        let contents = serde_json::to_vec_pretty(&vault.file)?;
+        OpenOptions::new().write(true).create_new(true).open(&vault.path)?.write_all(&contents)?;
+        Ok(vault)
+    }
+
+    /// Opens the vault at `path`. A wrong passphrase and a tampered file are told apart from a missing one.
+    pub fn open(path: impl AsRef<Path>, passphrase: &str) -> Result<Vault> {
+        let path = path.as_ref().to_path_buf();
+        let file = serde_json::from_slice::<VaultFile>(&std::fs::read(&path)?)?;
+        if file.version != VERSION {
+            return Err(Error::Vault(format!("unsupported vault version {}", file.version)));
+        }
+
+        let wrapping_key = derive_key(passphrase, &decode(&file.salt)?, file.kdf)?;
+        let key = unseal(&wrapping_key, KEY_AAD, &file.key)
+            .map_err(|_| Error::Vault("wrong passphrase or corrupted vault".to_string()))?;
+        let key = <[u8; 32]>::try_from(key.as_slice())
+            .map_err(|_| Error::Vault("corrupted data key".to_string()))?;
+        Ok(Vault { path, file, key: Zeroizing::new(key) })
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.file.entries.keys().map(String::as_str)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.file.entries.contains_key(name)
+    }
+
+    /// Decrypts the credentials stored as `name`.
+    pub fn get(&self, name: &str) -> Result<Cookies> {
+        let sealed = self.file.entries.get(name)
+            .ok_or_else(|| Error::Vault(format!("no entry named `{}`", name)))?;
+        let entry = unseal(&self.key, name.as_bytes(), sealed)?;
+        serde_json::from_slice(&entry).map_err(|why| Error::Vault(format!("entry `{}` is corrupted: {}", name, why)))
+    }
+
+    /// Stores `cookies` as `name` along with their expiry, replacing the entry of the same name.
+    pub fn insert(&mut self, name: impl Into<String>, cookies: &Cookies) -> Result<()> {
+        let name = name.into();
+        let entry = Zeroizing::new(serde_json::to_vec(cookies)?);
+        let sealed = seal(&self.key, name.as_bytes(), &entry)?;
+        self.file.entries.insert(name, sealed);
+        self.save()
+    }
+
+    /// Removes the entry `name`; returns whether there was one.
+    pub fn remove(&mut self, name: &str) -> Result<bool> {
+        if self.file.entries.remove(name).is_none() {
+            return Ok(false);
+        }
+        self.save()?;
+        Ok(true)
+    }
+
+    /// Re-encrypts the data key under a new passphrase. Entries are left untouched.
+    pub fn change_passphrase(&mut self, passphrase: &str) -> Result<()> {
+        let salt = random_bytes::<16>();
+        let wrapping_key = derive_key(passphrase, &salt, self.file.kdf)?;
+        self.file.salt = STANDARD.encode(salt);
+        self.file.key = seal(&wrapping_key, KEY_AAD, self.key.as_slice())?;
+        self.save()
+    }
+
+    /// Replaces the data key and re-encrypts every entry with it, one entry at a time.
+    /// The new key is sealed under `passphrase`, which may be the current one.
+    pub fn rotate_key(&mut self, passphrase: &str) -> Result<()> {
+        let key = Zeroizing::new(random_bytes::<32>());
+        let mut entries = BTreeMap::new();
+        for (name, sealed) in &self.file.entries {
+            let plaintext = unseal(&self.key, name.as_bytes(), sealed)?;
+            entries.insert(name.clone(), seal(&key, name.as_bytes(), &plaintext)?);
+        }
+
+        let salt = random_bytes::<16>();
+        let wrapping_key = derive_key(passphrase, &salt, self.file.kdf)?;
+        self.file.salt = STANDARD.encode(salt);
+        self.file.key = seal(&wrapping_key, KEY_AAD, key.as_slice())?;
+        self.file.entries = entries;
+        self.key = key;
+        self.save()
+    }
+
+    /// Writes the vault next to its file and moves it into place, so a crash never leaves half a vault behind.
+    fn save(&self) -> Result<()> {
+        let contents = serde_json::to_vec_pretty(&self.file)?;
+        let mut partial = self.path.clone().into_os_string();
+        partial.push(".partial");
+        std::fs::write(&partial, contents)?;
+        std::fs::rename(&partial, &self.path)?;
+        Ok(())
+    }
+}
+
+
+fn derive_key(passphrase: &str, salt: &[u8], kdf: KdfParams) -> Result<Zeroizing<[u8; 32]>> {
+    let params = Params::new(kdf.memory_kib, kdf.iterations, kdf.parallelism, Some(32))
+        .map_err(|why| Error::Vault(format!("invalid key derivation parameters: {}", why)))?;
+    let mut key = Zeroizing::new([0u8; 32]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, key.as_mut_slice())
+        .map_err(|why| Error::Vault(format!("key derivation failed: {}", why)))?;
+    Ok(key)
+}
+
+fn seal(key: &[u8; 32], aad: &[u8], plaintext: &[u8]) -> Result<Sealed> {
+    let nonce = random_bytes::<24>();
+    let ciphertext = XChaCha20Poly1305::new(Key::from_slice(key))
+        .encrypt(XNonce::from_slice(&nonce), Payload { msg: plaintext, aad })
+        .map_err(|_| Error::Vault("encryption failed".to_string()))?;
+    Ok(Sealed { nonce: STANDARD.encode(nonce), ciphertext: STANDARD.encode(ciphertext) })
+}
+
+fn unseal(key: &[u8; 32], aad: &[u8], sealed: &Sealed) -> Result<Zeroizing<Vec<u8>>> {
+    let nonce = decode(&sealed.nonce)?;
+    if nonce.len() != 24 {
+        return Err(Error::Vault("corrupted nonce".to_string()));
+    }
+    let plaintext = XChaCha20Poly1305::new(Key::from_slice(key))
+        .decrypt(XNonce::from_slice(&nonce), Payload { msg: &decode(&sealed.ciphertext)?, aad })
+        .map_err(|_| Error::Vault("entry failed authentication".to_string()))?;
+    Ok(Zeroizing::new(plaintext))
+}
+
+fn decode(value: &str) -> Result<Vec<u8>> {
+    STANDARD.decode(value).map_err(|why| Error::Vault(format!("corrupted vault: {}", why)))
+}
+
+fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seals_entries_and_rotates_keys() {
+        let kdf = KdfParams { memory_kib: 64, iterations: 1, parallelism: 1 };
+        let path = std::env::temp_dir().join(format!("mihoyo-vault-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut cookies = Cookies::parse("ltuid_v2=1000; ltoken_v2=v2_secret; cookie_token_v2=v2_ct").unwrap();
+        cookies.expires_at = Some(std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_900_000_000));
+
+        let mut vault = Vault::create_with(&path, "hunter2", kdf).unwrap();
+        assert!(Vault::create_with(&path, "hunter2", kdf).is_err());
+        vault.insert("alice", &cookies).unwrap();
+        vault.insert("bob", &Cookies::parse("ltuid=2000; ltoken=lt").unwrap()).unwrap();
+        assert!(!std::fs::read_to_string(&path).unwrap().contains("v2_secret"));
+
+        assert!(matches!(Vault::open(&path, "wrong"), Err(Error::Vault(_))));
+        let mut vault = Vault::open(&path, "hunter2").unwrap();
+        assert_eq!(vault.get("alice").unwrap(), cookies);
+        assert!(vault.remove("bob").unwrap());
+        assert!(!vault.remove("bob").unwrap());
+
+        vault.rotate_key("correct horse").unwrap();
+        vault.change_passphrase("battery staple").unwrap();
+        assert!(Vault::open(&path, "hunter2").is_err());
+        let vault = Vault::open(&path, "battery staple").unwrap();
+        assert_eq!(vault.names().collect::<Vec<_>>(), ["alice"]);
+        assert_eq!(vault.get("alice").unwrap(), cookies);
+
+        // An entry moved under another name no longer authenticates.
+        let mut file = serde_json::from_slice::<VaultFile>(&std::fs::read(&path).unwrap()).unwrap();
+        let sealed = file.entries.remove("alice").unwrap();
+        file.entries.insert("mallory".to_string(), sealed);
+        std::fs::write(&path, serde_json::to_vec(&file).unwrap()).unwrap();
+        assert!(Vault::open(&path, "battery staple").unwrap().get("mallory").is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
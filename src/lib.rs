@@ -1,3 +1,5 @@
+#![allow(non_snake_case)]
+
 pub mod client;
 pub mod model;
 pub mod util;
@@ -0,0 +1,9 @@
+use serde::Deserialize;
+
+
+#[derive(Debug, Deserialize)]
+pub struct DeviceFp {
+    pub device_fp: String,
+    pub code: i32,
+    pub msg: String,
+}
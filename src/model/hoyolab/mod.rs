@@ -1 +1,2 @@
+pub mod device;
 pub mod record;
\ No newline at end of file
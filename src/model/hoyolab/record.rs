@@ -1,5 +1,8 @@
+use std::collections::HashMap;
+use std::time::Duration;
 use serde::Deserialize;
-use crate::util::types::{Region, Game};
+use crate::util::error::{Error, Result};
+use crate::util::types::Game;
 
 
 #[derive(Debug,
@@ -23,19 +26,48 @@ pub struct Account {
     pub is_official: bool,
 }
 impl Account {
-    pub fn which_game(&self) -> Game {
-        match self.game_biz.as_str() {
-            "hk4e_global" => Game::GENSHIN,
-            "bh3_global" => Game::HONKAI,
-            _ => Game::STARRAIL
+    /// The game of the account, from the `game_biz` prefix shared by the overseas and mainland servers,
+    /// e.g. `hkrpg_global` and `hkrpg_cn`. `None` for games this crate doesn't know.
+    pub fn which_game(&self) -> Option<Game> {
+        match self.game_biz.split('_').next() {
+            Some("hk4e") => Some(Game::GENSHIN),
+            Some("bh3") => Some(Game::HONKAI),
+            Some("hkrpg") => Some(Game::STARRAIL),
+            _ => None,
         }
     }
-    pub fn get_uid(&self) -> u32 {
-        self.uid.parse().unwrap()
+    pub fn get_uid(&self) -> Result<u32> {
+        self.uid.parse().map_err(|_| Error::InvalidUid(self.uid.clone()))
     }
 }
 
 
+/// The part of `getUserFullInfo` that identifies the HoYoLAB account.
+#[derive(Debug, Deserialize)]
+pub(crate) struct UserFullInfo {
+    pub(crate) user_info: UserInfo,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct UserInfo {
+    pub(crate) uid: String,
+}
+
+
+/// What HoYoLAB says about a set of credentials.
+#[derive(Debug)]
+pub struct CredentialStatus {
+    /// From the HoYoLAB user info API, or else from the `ltuid`/`account_id` cookie; `None` when neither has it.
+    pub hoyolab_id: Option<u32>,
+    /// The game accounts bound to the credentials, keyed by server, e.g. `prod_official_asia`.
+    pub roles: HashMap<String, Vec<Account>>,
+    /// How long the tokens stay valid. HoYoLAB only tells when it refreshes a token through `Set-Cookie`,
+    /// so this is `None` for cookies that were never refreshed: unknown, not unlimited.
+    pub expires_in: Option<Duration>,
+}
+
+
+#[allow(unused)]
 #[derive(Debug, Deserialize)]
 pub struct RecordCards {
     pub retcode: u32,
@@ -1,14 +1,42 @@
 use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use crate::util::error::{Error, Result};
 
 pub(crate) mod genshin;
 pub(crate) mod honkai;
 pub(crate) mod hoyolab;
+#[cfg(feature = "starrail")]
 pub(crate) mod starrail;
 
 
 #[derive(Debug, Deserialize)]
 pub(crate) struct ModelBase<T> {
-    retcode: u32,
+    retcode: i32,
     message: String,
-    pub(crate) data: T
-}
\ No newline at end of file
+    pub(crate) data: Option<T>
+}
+
+impl ModelBase<serde_json::Value> {
+    /// Checks the envelope first so that `data: null` replies surface as their retcode error.
+    pub(crate) fn into_data<T: DeserializeOwned>(self) -> Result<T> {
+        if self.retcode != 0 {
+            return Err(Error::from_retcode(self.retcode, self.message));
+        }
+        let data = self.data.unwrap_or(serde_json::Value::Null);
+        Ok(serde_json::from_value(data)?)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::hoyolab::record::AccountList;
+
+    #[test]
+    fn null_data_becomes_api_error() {
+        let body = r#"{"retcode":10102,"message":"Data is not public for the user","data":null}"#;
+        let envelope = serde_json::from_str::<ModelBase<serde_json::Value>>(body).unwrap();
+        assert!(matches!(envelope.into_data::<AccountList>(), Err(Error::DataNotPublic { .. })));
+    }
+}
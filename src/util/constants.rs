@@ -15,6 +15,8 @@ pub(crate) static DS_SALT: Lazy<HashMap<Region,
     map.insert(Region::CHINESE, "xV8v4Qu54lUKrEYFZkJhB8cuOh9Asafs");
     map
 });
+/// Mainland daily check-in signs with its own salt.
+pub(crate) static DS_SALT_CN_SIGNIN: &str = "9nQiU3AV0rJSIBWgdynfoGMGKaklfbM7";
 
 pub(crate) static UID_RANGE: Lazy<HashMap<Game,
  HashMap<Region,
@@ -47,6 +49,8 @@ pub(crate) static UID_RANGE: Lazy<HashMap<Game,
 pub(crate) static USER_AGENT: &str = "Mozilla/5.0 (iPhone; CPU iPhone OS 13_2_3 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) miHoYoBBS/2.11.1)";
 
 
+// Routes marked `dead_code` are declared ahead of the endpoints that will use them.
+#[allow(dead_code)]
 pub(crate) static WEB_STATIC_URL: Lazy<InternationalRoute> = Lazy::new(|| InternationalRoute::new(
     "https://webstatic-sea.hoyoverse.com/",
     "https://webstatic.mihoyo.com/"
@@ -63,6 +67,7 @@ pub(crate) static BBS_URL: Lazy<InternationalRoute> = Lazy::new(|| International
     "https://bbs-api-os.hoyolab.com/",
     "https://bbs-api.mihoyo.com/",
 ));
+#[allow(dead_code)]
 pub(crate) static BBS_REFERER_URL: Lazy<InternationalRoute> = Lazy::new(|| InternationalRoute::new(
     "https://www.hoyolab.com/",
     "https://bbs.mihoyo.com/",
@@ -79,10 +84,16 @@ pub(crate) static RECORD_URL: Lazy<InternationalRoute> = Lazy::new(|| Internatio
     "https://bbs-api-os.hoyolab.com/game_record/",
     "https://api-takumi-record.mihoyo.com/game_record/app/",
 ));
+pub(crate) static DEVICE_FP_URL: Lazy<InternationalRoute> = Lazy::new(|| InternationalRoute::new(
+    "https://sg-public-data-api.hoyoverse.com/device-fp/api/getFp",
+    "https://public-data-api.mihoyo.com/device-fp/api/getFp",
+));
+#[allow(dead_code)]
 pub(crate) static LINEUP_URL: Lazy<InternationalRoute> = Lazy::new(|| InternationalRoute::new(
     "https://sg-pub(crate)lic-api.hoyoverse.com/event/simulatoros/",
     "https://api-takumi.mihoyo.com/event/platsimulator/",
 ));
+#[allow(dead_code)]
 pub(crate) static INFO_LEDGER_URL: Lazy<GameRoute> = Lazy::new(|| GameRoute::new(
     Some(&[
         (GENSHIN, "https://sg-hk4e-api.hoyolab.com/event/ysledgeros/month_info"),
@@ -93,6 +104,7 @@ pub(crate) static INFO_LEDGER_URL: Lazy<GameRoute> = Lazy::new(|| GameRoute::new
         (STARRAIL, "https://api-takumi.mihoyo.com/event/srledger/month_info")
     ])
 ));
+#[allow(dead_code)]
 pub(crate) static DETAIL_LEDGER_URL: Lazy<GameRoute> = Lazy::new(|| GameRoute::new(
     Some(&[
         (GENSHIN, "https://sg-hk4e-api.hoyolab.com/event/ysledgeros/month_detail"),
@@ -103,17 +115,23 @@ pub(crate) static DETAIL_LEDGER_URL: Lazy<GameRoute> = Lazy::new(|| GameRoute::n
         (STARRAIL, "https://api-takumi.mihoyo.com/event/srledger/month_detail"),
     ])
 ));
+#[allow(dead_code)]
 pub(crate) static CALCULATOR_URL: Lazy<InternationalRoute> = Lazy::new(|| InternationalRoute::new(
     "https://sg-pub(crate)lic-api.hoyoverse.com/event/calculateos/",
     "https://api-takumi.mihoyo.com/event/e20200928calculate/v1/",
 ));
+#[allow(dead_code)]
 pub(crate) static CALCULATOR_REFERER_URL: Lazy<Route> = Lazy::new(|| Route::new("https://webstatic.mihoyo.com/ys/event/e20200923adopt_calculator/index.html"));
+#[allow(dead_code)]
 pub(crate) static TEAPOT_URL: Lazy<InternationalRoute> = Lazy::new(|| InternationalRoute::new(
     "https://sg-hk4e-api.hoyolab.com/event/e20221121ugcos/",
     "",
 ));
+#[allow(dead_code)]
 pub(crate) static WIKI_URL: Lazy<Route> = Lazy::new(|| Route::new("https://sg-wiki-api.hoyolab.com/hoyowiki/wapi"));
+#[allow(dead_code)]
 pub(crate) static HK4E_URL: Lazy<Route> = Lazy::new(|| Route::new("https://sg-hk4e-api.hoyoverse.com/common/hk4e_global/"));
+#[allow(dead_code)]
 pub(crate) static REWARD_URL: Lazy<GameRoute> = Lazy::new(|| GameRoute::new(
     Some(&[
         (GENSHIN, "https://sg-hk4e-api.hoyolab.com/event/sol?act_id=e202102251931481"),
@@ -126,6 +144,7 @@ pub(crate) static REWARD_URL: Lazy<GameRoute> = Lazy::new(|| GameRoute::new(
         (STARRAIL, "https://api-takumi.mihoyo.com/event/luna/?act_id=e202304121516551"),
     ])
 ));
+#[allow(dead_code)]
 pub(crate) static CODE_URL: Lazy<GameRoute> = Lazy::new(|| GameRoute::new(
     Some(&[
         (GENSHIN, "https://sg-hk4e-api.hoyoverse.com/common/apicdkey/api/webExchangeCdkey"),
@@ -133,6 +152,7 @@ pub(crate) static CODE_URL: Lazy<GameRoute> = Lazy::new(|| GameRoute::new(
     ]),
     None,
 ));
+#[allow(dead_code)]
 pub(crate) static GACHA_URL: Lazy<GameRoute> = Lazy::new(|| GameRoute::new(
     Some(&[
         (GENSHIN, "https://hk4e-api-os.hoyoverse.com/event/gacha_info/api/"),
@@ -143,10 +163,12 @@ pub(crate) static GACHA_URL: Lazy<GameRoute> = Lazy::new(|| GameRoute::new(
         (STARRAIL, "https://api-takumi.mihoyo.com/common/gacha_record/api/")
     ])
 ));
+#[allow(dead_code)]
 pub(crate) static YSULOG_URL: Lazy<InternationalRoute> = Lazy::new(|| InternationalRoute::new(
     "https://hk4e-api-os.hoyoverse.com/common/hk4e_self_help_query/User/",
     "https://hk4e-api.mihoyo.com/common/hk4e_self_help_query/User/"
 ));
+#[allow(dead_code)]
 pub(crate) static MI18N: Lazy<HashMap<&'_ str,
  &'_ str>> = Lazy::new(|| HashMap::from(
     [(
@@ -3,6 +3,7 @@ use once_cell::sync::Lazy;
 
 
 
+#[allow(unused)]
 pub(crate) static LANGUAGES: Lazy<HashMap<&'static str,
  &'static str>> = Lazy::new(||
      HashMap::from(
@@ -0,0 +1,181 @@
+use std::fmt::Debug;
+use std::ops::RangeInclusive;
+use std::time::SystemTime;
+use rand::{self, Rng};
+use reqwest::header::{HeaderMap, HeaderValue};
+use crate::util::secret::sensitive_header;
+use crypto::digest::Digest;
+use crypto::md5;
+use crate::client::ClientIdentity;
+use crate::util::error::Result;
+use crate::util::types;
+
+
+/// The time a dynamic secret is stamped with, in seconds since the unix epoch.
+pub trait Clock: Debug + Send + Sync {
+    fn now(&self) -> u64;
+}
+
+/// The randomness of a dynamic secret.
+pub trait RandomSource: Debug + Send + Sync {
+    fn gen_range(&self, range: RangeInclusive<u32>) -> u32;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ThreadRandom;
+
+impl RandomSource for ThreadRandom {
+    fn gen_range(&self, range: RangeInclusive<u32>) -> u32 {
+        rand::thread_rng().gen_range(range)
+    }
+}
+
+
+/// The overseas DS: `t,r,md5(salt={salt}&t={t}&r={r})` with six random letters as `r`.
+pub fn generate_dynamic_secret(salt: &str, clock: &dyn Clock, rng: &dyn RandomSource) -> String {
+    const CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+    let t = clock.now();
+    let r = (0..6)
+        .map(|_| {
+            let idx = rng.gen_range(0..=CHARS.len() as u32 - 1) as usize;
+            char::from(CHARS[idx % CHARS.len()])
+        })
+        .collect::<String>();
+    let mut h = md5::Md5::new();
+    h.input(format!("salt={}&t={}&r={}", salt, t, r).as_bytes());
+
+    format!("{},{},{}", t, r, h.result_str())
+}
+
+/// The mainland DS: besides salt, time and random it signs the JSON body and the sorted query.
+pub fn generate_cn_dynamic_secret(body: Option<&str>, query: &[(String, String)], salt: &str, clock: &dyn Clock, rng: &dyn RandomSource) -> String {
+    let t = clock.now();
+    let r = rng.gen_range(100001..=200000);
+    let b = body.unwrap_or_default();
+    let mut query = query.iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>();
+    query.sort();
+    let q = query.join("&");
+
+    let mut h = md5::Md5::new();
+    h.input(format!("salt={}&t={}&r={}&b={}&q={}", salt, t, r, b, q).as_bytes());
+
+    format!("{},{},{}", t, r, h.result_str())
+}
+
+
+/// Mainland endpoints are grouped by the salt and app version their DS is checked against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DsFamily {
+    /// Battle chronicle, account bindings and the rest of takumi: DS2 over the body and query.
+    Record,
+    /// Daily check-in rewards.
+    SignIn,
+}
+
+impl DsFamily {
+    pub(crate) fn of(url: &str) -> DsFamily {
+        if url.contains("bbs_sign_reward") || url.contains("/event/luna/") {
+            DsFamily::SignIn
+        } else {
+            DsFamily::Record
+        }
+    }
+}
+
+
+pub(crate) fn get_ds_headers(
+    identity: &ClientIdentity,
+    region: &types::Region,
+    family: DsFamily,
+    data: Option<&str>,
+    params: &[(String, String)],
+    lang: Option<&str>,
+) -> Result<HeaderMap> {
+    let (clock, rng) = (identity.clock.as_ref(), identity.rng.as_ref());
+    let mut map = HeaderMap::new();
+    match region {
+        types::Region::OVERSEAS => {
+            map.insert("x-rpc-app_version", HeaderValue::from_str(&identity.os_app_version)?);
+            map.insert("x-rpc-client_type", HeaderValue::from_static("5"));
+            map.insert("x-rpc-language", HeaderValue::from_str(lang.unwrap_or("None"))?);
+            map.insert("ds", sensitive_header(&generate_dynamic_secret(&identity.os_salt, clock, rng))?);
+            Ok(map)
+        }
+        types::Region::CHINESE => {
+            map.insert("x-rpc-client_type", HeaderValue::from_static("5"));
+            match family {
+                DsFamily::Record => {
+                    map.insert("x-rpc-app_version", HeaderValue::from_str(&identity.cn_app_version)?);
+                    let ds = generate_cn_dynamic_secret(data, params, &identity.cn_salt, clock, rng);
+                    map.insert("ds", sensitive_header(&ds)?);
+                }
+                DsFamily::SignIn => {
+                    map.insert("x-rpc-app_version", HeaderValue::from_str(&identity.cn_signin_app_version)?);
+                    let ds = generate_dynamic_secret(&identity.cn_signin_salt, clock, rng);
+                    map.insert("ds", sensitive_header(&ds)?);
+                }
+            }
+            Ok(map)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct Fixed;
+
+    impl Clock for Fixed {
+        fn now(&self) -> u64 {
+            1690000000
+        }
+    }
+
+    impl RandomSource for Fixed {
+        fn gen_range(&self, range: RangeInclusive<u32>) -> u32 {
+            27u32.clamp(*range.start(), *range.end())
+        }
+    }
+
+    #[test]
+    fn dynamic_secret_is_reproducible() {
+        let ds = generate_dynamic_secret("6s25p5ox5y14umn1p61aqyyvbvvl3lrt", &Fixed, &Fixed);
+        assert_eq!(ds, "1690000000,bbbbbb,58d32a40d4a8559419dbed6b8b88b6cf");
+    }
+
+    #[test]
+    fn cn_dynamic_secret_signs_body_and_sorted_query() {
+        let body = r#"{"role_id":"100000001"}"#;
+        let query = vec![("server".to_string(), "prod_gf_cn".to_string()), ("role_id".to_string(), "100000001".to_string())];
+        let ds = generate_cn_dynamic_secret(Some(body), &query, "salt", &SystemClock, &ThreadRandom);
+
+        let parts = ds.split(',').collect::<Vec<_>>();
+        let mut h = md5::Md5::new();
+        h.input(format!("salt=salt&t={}&r={}&b={}&q=role_id=100000001&server=prod_gf_cn", parts[0], parts[1], body).as_bytes());
+        assert_eq!(parts[2], h.result_str());
+    }
+
+    #[test]
+    fn mainland_families_use_their_own_app_version() {
+        let url = "https://api-takumi.mihoyo.com/event/luna/sign?act_id=e202304121516551";
+        let headers = get_ds_headers(&ClientIdentity::default(), &types::Region::CHINESE, DsFamily::of(url), None, &[], None).unwrap();
+        assert_eq!(headers["x-rpc-app_version"], "2.34.1");
+
+        let url = "https://api-takumi-record.mihoyo.com/game_record/app/hkrpg/api/note";
+        let headers = get_ds_headers(&ClientIdentity::default(), &types::Region::CHINESE, DsFamily::of(url), None, &[], None).unwrap();
+        assert_eq!(headers["x-rpc-app_version"], "2.11.1");
+        assert!(headers.contains_key("ds"));
+    }
+}
@@ -0,0 +1,143 @@
+use std::path::PathBuf;
+use std::time::SystemTime;
+use reqwest::header::InvalidHeaderValue;
+use reqwest::StatusCode;
+use crate::client::captcha::CaptchaChallenge;
+use crate::util::types::Game;
+
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The request could not be sent or the response could not be read.
+    #[error("transport error: {0}")]
+    Transport(#[from] reqwest::Error),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    /// The server answered with a non-success status and no readable envelope.
+    #[error("unexpected http status: {0}")]
+    Status(StatusCode),
+    /// The response body was not the JSON we expected.
+    #[error("failed to decode response: {0}")]
+    Decode(#[from] serde_json::Error),
+    #[error("failed to encode request parameters: {0}")]
+    Encode(#[from] serde_urlencoded::ser::Error),
+    /// A cassette could not be read or holds no answer for the request.
+    #[error("cassette error: {0}")]
+    Cassette(String),
+    /// A cookie header that couldn't be parsed; cookies HoYoLAB rejects are [`Error::InvalidCookies`].
+    #[error("malformed cookie: {0}")]
+    MalformedCookie(String),
+    #[error("invalid proxy: {0}")]
+    InvalidProxy(String),
+    #[error("invalid header value: {0}")]
+    InvalidHeader(#[from] InvalidHeaderValue),
+    /// An url could not be built for the requested region/game.
+    #[error("route error: {0}")]
+    Route(String),
+    #[error("unable to recognize the {game:?} server of uid {uid}")]
+    UidRecognition { uid: u32, game: Game },
+    /// A game uid that isn't a number, as HoYoLAB sends them as strings.
+    #[error("invalid uid `{0}`")]
+    InvalidUid(String),
+    /// A value the request needs (cookies, uid, hoyolab id ...) was not provided.
+    #[error("`{0}` is not configured")]
+    NotConfigured(&'static str),
+    #[error("no {0:?} account is bound to these cookies")]
+    NoGameAccount(Game),
+    /// A credential file could not be read, parsed or written.
+    #[error("invalid credential file {}: {reason}", path.display())]
+    Config { path: PathBuf, reason: String },
+    #[error("no profile named `{0}`")]
+    ProfileNotFound(String),
+    /// The credential vault could not be opened, decrypted or written.
+    #[error("vault error: {0}")]
+    Vault(String),
+    /// Every credential of the cookie pool is cooling down or was rejected.
+    #[error("no credential of the cookie pool is available")]
+    PoolExhausted,
+    /// The cookies have to be renewed rather than fixed: either their known expiry has passed,
+    /// or HoYoLAB answered with `retcode` that the login has expired.
+    #[error("the cookies have expired")]
+    ExpiredCookies { expired_at: Option<SystemTime>, retcode: Option<i32> },
+
+    // Errors reported by the API through `retcode`.
+    #[error("[{retcode}] invalid cookies: {message}")]
+    InvalidCookies { retcode: i32, message: String },
+    #[error("[{retcode}] data is not public: {message}")]
+    DataNotPublic { retcode: i32, message: String },
+    #[error("[{retcode}] too many requests: {message}")]
+    TooManyRequests { retcode: i32, message: String },
+    /// `challenge` is filled in when the geetest challenge could be fetched.
+    #[error("[{retcode}] captcha required: {message}")]
+    CaptchaRequired { retcode: i32, message: String, challenge: Option<CaptchaChallenge> },
+    #[error("[{retcode}] invalid authkey: {message}")]
+    InvalidAuthkey { retcode: i32, message: String },
+    #[error("[{retcode}] authkey timed out: {message}")]
+    AuthkeyTimeout { retcode: i32, message: String },
+    #[error("[{retcode}] account not found: {message}")]
+    AccountNotFound { retcode: i32, message: String },
+    #[error("[{retcode}] {message}")]
+    Api { retcode: i32, message: String },
+}
+
+
+impl Error {
+    /// Maps a non-zero `retcode` of the response envelope to its error variant.
+    pub fn from_retcode(retcode: i32, message: String) -> Error {
+        match retcode {
+            -100 if message.to_lowercase().contains("authkey") => Error::InvalidAuthkey { retcode, message },
+            -101 => Error::AuthkeyTimeout { retcode, message },
+            -100 | 10001 if is_expired_login(&message) => Error::ExpiredCookies { expired_at: None, retcode: Some(retcode) },
+            -100 | 10001 | 10103 => Error::InvalidCookies { retcode, message },
+            10101 | -110 => Error::TooManyRequests { retcode, message },
+            10102 => Error::DataNotPublic { retcode, message },
+            1034 => Error::CaptchaRequired { retcode, message, challenge: None },
+            1008 | 1009 => Error::AccountNotFound { retcode, message },
+            _ => Error::Api { retcode, message },
+        }
+    }
+
+    /// The API retcode behind this error, if it came from the response envelope.
+    pub fn retcode(&self) -> Option<i32> {
+        match self {
+            Error::InvalidCookies { retcode, .. }
+            | Error::DataNotPublic { retcode, .. }
+            | Error::TooManyRequests { retcode, .. }
+            | Error::CaptchaRequired { retcode, .. }
+            | Error::InvalidAuthkey { retcode, .. }
+            | Error::AuthkeyTimeout { retcode, .. }
+            | Error::AccountNotFound { retcode, .. }
+            | Error::Api { retcode, .. } => Some(*retcode),
+            Error::ExpiredCookies { retcode, .. } => *retcode,
+            _ => None,
+        }
+    }
+}
+
+/// HoYoLAB reports expired and rejected logins under the same retcodes; only the message tells them apart.
+fn is_expired_login(message: &str) -> bool {
+    let message = message.to_lowercase();
+    ["expired", "失效", "过期", "過期"].iter().any(|marker| message.contains(marker))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_known_retcodes() {
+        assert!(matches!(Error::from_retcode(-100, "Please login".into()), Error::InvalidCookies { .. }));
+        assert!(matches!(Error::from_retcode(10001, "Please login".into()), Error::InvalidCookies { .. }));
+        assert!(matches!(Error::from_retcode(-100, "authkey error".into()), Error::InvalidAuthkey { .. }));
+        assert!(matches!(Error::from_retcode(-100, "Login expired, please log in again".into()), Error::ExpiredCookies { retcode: Some(-100), .. }));
+        assert!(matches!(Error::from_retcode(-100, "登录失效，请重新登录".into()), Error::ExpiredCookies { .. }));
+        assert!(matches!(Error::from_retcode(10101, String::new()), Error::TooManyRequests { .. }));
+        assert!(matches!(Error::from_retcode(10102, String::new()), Error::DataNotPublic { .. }));
+        assert!(matches!(Error::from_retcode(1034, String::new()), Error::CaptchaRequired { .. }));
+        assert_eq!(Error::from_retcode(-1, String::new()).retcode(), Some(-1));
+    }
+}
@@ -1,6 +1,7 @@
 pub mod constants;
 pub mod contain;
+pub mod ds;
 pub mod error;
-pub mod kwargs;
+pub mod secret;
 pub mod types;
 pub mod uid;
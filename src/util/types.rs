@@ -1,29 +1,35 @@
-use std::any::Any;
 use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
 
 pub(crate) type GeneralResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+#[allow(unused)]
 pub(crate) type NaturalDict<'a> = HashMap<&'a str, &'a str>;
 pub(crate) type StringDict = HashMap<String, String>;
+#[allow(unused)]
 pub(crate) type ResultDict<'a> = GeneralResult<NaturalDict<'a>>;
-pub(crate) type GeneralAny = Box<dyn Any + Send + Sync>;
+#[allow(unused)]
 pub(crate) type Params<'a, T> = HashMap<&'a str, T>;
 
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, PartialEq)]
 pub(crate) enum CookieOrHeader {
     // "http.cookies.BaseCookie[typing.Any]"  https://github.com/thesadru/genshin.py/blob/de07439215f9390a3c1a5bdbe5ff5902e6608dd7/genshin/client/manager/managers.py#L29
     Dict(StringDict),
     // Str(&'a str)
 }
 
-#[derive(Debug, PartialEq)]
-pub(crate) enum AnyCookieOrHeader {
-    CookieOrHeader(CookieOrHeader),
-    SequenceCookieOrHeader(Vec<CookieOrHeader>)
+/// Only the cookie names are printed.
+impl std::fmt::Debug for CookieOrHeader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CookieOrHeader::Dict(dict) => f.debug_tuple("Dict").field(&dict.keys().collect::<Vec<_>>()).finish(),
+        }
+    }
 }
 
 
-#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Region {
     OVERSEAS,
     CHINESE,
@@ -37,7 +43,8 @@ impl Region {
     }
 }
 
-#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Copy, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Game {
     GENSHIN,
     HONKAI,
@@ -1,8 +1,9 @@
 use super::types::{Game, Region};
 use super::constants::UID_RANGE;
+use super::error::{Error, Result};
 
-pub(crate) fn recognize_genshin_server(uid: &u32) -> anyhow::Result<String> {
-    let server = match uid.to_string().char_indices().nth(0).unwrap().1.to_string().as_str() {
+pub(crate) fn recognize_genshin_server(uid: &u32) -> Result<String> {
+    let server = match &uid.to_string()[0..1] {
         "1" => "cn_gf01",
         "2" => "cn_gf01",
         "5" => "cn_qd01",
@@ -10,14 +11,14 @@ pub(crate) fn recognize_genshin_server(uid: &u32) -> anyhow::Result<String> {
         "7" => "os_euro",
         "8" => "os_asia",
         "9" => "os_cht",
-        _ => anyhow::bail!("uid doesn't much the server number like smthing"),
+        _ => return Err(Error::UidRecognition { uid: *uid, game: Game::GENSHIN }),
     };
     Ok(server.to_string())
 }
 
 
-pub(crate) fn recognize_honkai_server(uid: &u32) -> anyhow::Result<String> {
-    let uid = uid.clone();
+pub(crate) fn recognize_honkai_server(uid: &u32) -> Result<String> {
+    let uid = *uid;
     let server = if 100000 < uid && uid < 1000000 {
         "overseas01"
     } else if 1000000 < uid && uid < 2000000 {
@@ -25,14 +26,14 @@ pub(crate) fn recognize_honkai_server(uid: &u32) -> anyhow::Result<String> {
     } else if 2000000 < uid && uid < 3000000 {
         "eur01"
     } else {
-        anyhow::bail!("uid doesn't much the server number like smthing")
+        return Err(Error::UidRecognition { uid, game: Game::HONKAI })
     };
     Ok(server.to_string())
 }
 
 
-pub(crate) fn recognize_starrail_server(uid: &u32) -> anyhow::Result<String> {
-    let server = match uid.to_string().char_indices().nth(0).unwrap().1.to_string().as_str() {
+pub(crate) fn recognize_starrail_server(uid: &u32) -> Result<String> {
+    let server = match &uid.to_string()[0..1] {
         "1" => "prod_gf_cn",
         "2" => "prod_gf_cn",
         "5" => "prod_qd_cn",
@@ -40,14 +41,14 @@ pub(crate) fn recognize_starrail_server(uid: &u32) -> anyhow::Result<String> {
         "7" => "prod_official_eur",
         "8" => "prod_official_asia",
         "9" => "prod_official_cht",
-        _ => anyhow::bail!("uid doesn't much the server number like smthing"),
+        _ => return Err(Error::UidRecognition { uid: *uid, game: Game::STARRAIL }),
     };
     Ok(server.to_string())
 }
 
 
 pub(crate) fn recognize_server(uid: &u32,
- game: Game) -> anyhow::Result<String> {
+ game: Game) -> Result<String> {
     match game {
         Game::GENSHIN => {
             recognize_genshin_server(uid)
@@ -63,12 +64,12 @@ pub(crate) fn recognize_server(uid: &u32,
 
 
 
-pub(crate) fn recognize_region(uid: &mut u32,
+pub(crate) fn recognize_region(uid: &u32,
  game: Game) -> Option<Region> {
-    let first = &uid.to_string()[0..1].parse::<u8>().unwrap();
-    for (region, dict) in UID_RANGE.get(&game).unwrap() {
-        if dict.contains(first) {
-            return Some(region.clone())
+    let first = uid.to_string()[0..1].parse::<u8>().ok()?;
+    for (region, dict) in UID_RANGE.get(&game)? {
+        if dict.contains(&first) {
+            return Some(*region)
         }
     }
     None